@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use reqwest::Method;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    coinm_futures::types::{request, response},
+    error::Result,
+    rest::RestClient,
+};
+
+pub use crate::rest::{MetricsCallback, RetryPolicy};
+
+const BASE_URL: &str = "https://dapi.binance.com";
+const TIME_ENDPOINT: &str = "dapi/v1/time";
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    rest: RestClient,
+}
+
+impl Client {
+    pub fn new(key: Option<&str>, secret: Option<&str>, proxy: Option<&str>) -> Result<Self> {
+        let rest = RestClient::new(BASE_URL, TIME_ENDPOINT, key, secret, proxy)?;
+        Ok(Client { rest })
+    }
+
+    /// Same as `new` with a pre-built `reqwest::Client`, e.g. with custom TLS roots, HTTP/2
+    /// tuning or a connection pool shared by clients. The proxy is configured on the client.
+    pub fn from_reqwest(client: reqwest::Client, key: Option<&str>, secret: Option<&str>) -> Self {
+        let rest = RestClient::from_reqwest(BASE_URL, TIME_ENDPOINT, client, key, secret);
+        Client { rest }
+    }
+
+    /// Enable retry of failed requests
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.rest = self.rest.with_retry(policy);
+        self
+    }
+
+    /// Report the cost of successful calls, e.g. to metrics, see `MetricsCallback`
+    pub fn with_metrics(
+        mut self,
+        callback: impl Fn(&str, Duration, Duration, Duration) + Send + Sync + 'static,
+    ) -> Self {
+        self.rest = self.rest.with_metrics(callback);
+        self
+    }
+
+    /// Sync the offset between local time and binance server time, the offset is applied to
+    /// the timestamp of signed requests.
+    pub async fn sync_time(&self) -> Result<()> {
+        self.rest.sync_time().await
+    }
+
+    fn endpoint(endpoint: &str) -> String {
+        format!("dapi/v1/{endpoint}")
+    }
+
+    async fn call<REQ, RESP>(&self, endpoint: &str, method: Method, req: REQ) -> Result<RESP>
+    where
+        REQ: Serialize,
+        RESP: DeserializeOwned,
+    {
+        self.rest.call(&Self::endpoint(endpoint), method, req).await
+    }
+
+    async fn signed_call<REQ, RESP>(&self, endpoint: &str, method: Method, req: REQ) -> Result<RESP>
+    where
+        REQ: Serialize,
+        RESP: DeserializeOwned,
+    {
+        self.rest
+            .signed_call(&Self::endpoint(endpoint), method, req)
+            .await
+    }
+}
+
+// market data
+impl Client {
+    /// Check server time
+    pub async fn check_server_time(&self) -> Result<response::ServerTime> {
+        self.call("time", Method::GET, None::<()>).await
+    }
+
+    pub async fn exchange_info(&self) -> Result<response::ExchangeInfo> {
+        self.call("exchangeInfo", Method::GET, None::<()>).await
+    }
+
+    /// Latest price of symbols of the pair, or all symbols if pair is not sent
+    pub async fn ticker_price(
+        &self,
+        params: request::OptionalPair,
+    ) -> Result<Vec<response::TickerPrice>> {
+        self.call("ticker/price", Method::GET, params).await
+    }
+
+    /// Kline/candlestick bars for a symbol
+    pub async fn klines(
+        &self,
+        params: request::KlineCandlestickData,
+    ) -> Result<Vec<response::KlineCandlestickData>> {
+        let v: Vec<serde_json::Value> = self.call("klines", Method::GET, params).await?;
+        v.into_iter().map(TryInto::try_into).collect()
+    }
+}
+
+// trade
+impl Client {
+    /// Send in a new order, the quantity is the number of contracts
+    pub async fn new_order(&self, params: request::NewOrder) -> Result<response::OrderInfo> {
+        params.validate()?;
+        self.signed_call("order", Method::POST, params).await
+    }
+
+    /// Cancel an active order.
+    pub async fn cancel_order(&self, params: request::OrderId) -> Result<response::OrderInfo> {
+        self.signed_call("order", Method::DELETE, params).await
+    }
+
+    /// Check an order's status
+    pub async fn query_order(&self, params: request::OrderId) -> Result<response::OrderInfo> {
+        self.signed_call("order", Method::GET, params).await
+    }
+
+    /// Get all account orders; active, canceled, or filled
+    pub async fn all_orders(&self, params: request::AllOrders) -> Result<Vec<response::OrderInfo>> {
+        self.signed_call("allOrders", Method::GET, params).await
+    }
+
+    /// Get all open orders on a symbol
+    pub async fn current_all_open_orders(
+        &self,
+        params: request::OptionalSymbol,
+    ) -> Result<Vec<response::OrderInfo>> {
+        self.signed_call("openOrders", Method::GET, params).await
+    }
+
+    /// Get current position information
+    pub async fn position_information(
+        &self,
+        params: request::OptionalPair,
+    ) -> Result<Vec<response::PositionInformation>> {
+        self.signed_call("positionRisk", Method::GET, params).await
+    }
+}
+
+// account
+impl Client {
+    /// Futures Account Balance
+    pub async fn futures_account_balance(&self) -> Result<Vec<response::AccountBalance>> {
+        self.signed_call("balance", Method::GET, None::<()>).await
+    }
+}
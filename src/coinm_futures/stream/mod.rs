@@ -0,0 +1,56 @@
+//! Coin-margined futures streams share the stream names and payloads of USDⓈ-M futures
+
+use std::ops::ControlFlow;
+
+use futures_channel::mpsc::UnboundedSender;
+use futures_util::stream::pending;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    error::Result,
+    log::error,
+    stream::{Exit, IDLE_TIMEOUT, SentCommands, close_error, connect, drive, idle_error},
+    usdm_futures::stream::check_stream_count,
+};
+
+pub use crate::usdm_futures::stream::{MAX_STREAMS, request, response};
+
+const URL: &str = "wss://dstream.binance.com/stream";
+
+pub async fn receive(
+    streams: Vec<request::Stream>,
+    tx: UnboundedSender<response::Stream>,
+    proxy: Option<&str>,
+) -> Result<()> {
+    check_stream_count(&streams)?;
+    let mut stream = connect(URL, proxy).await?;
+    let mut sent = SentCommands::default();
+    sent.send(&mut stream, &request::Command::Subscribe(streams))
+        .await?;
+    let exit = drive::<response::Response, _, _, _>(
+        &mut stream,
+        &CancellationToken::new(),
+        Some(IDLE_TIMEOUT),
+        &mut sent,
+        pending::<request::Command>(),
+        async |name, items| {
+            tx.unbounded_send(response::Stream::new(&name.unwrap_or_default(), items))?;
+            Ok(ControlFlow::Continue(()))
+        },
+    )
+    .await?;
+    match exit {
+        Exit::Error {
+            code,
+            msg,
+            id,
+            command,
+        } => {
+            error!("response error: id: {id}, code: {code}, message: {msg}, command: {command:?}");
+            Ok(())
+        }
+        Exit::Closed(Some(frame)) => Err(close_error(&frame)),
+        Exit::Idle(timeout) => Err(idle_error(timeout)),
+        Exit::Closed(None) | Exit::Stopped | Exit::Shutdown => Ok(()),
+    }
+}
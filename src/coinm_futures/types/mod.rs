@@ -0,0 +1,8 @@
+pub use crate::usdm_futures::types::{
+    ContractStatus, ContractType, KlineInterval, MarginType, OrderSide, OrderStatus, OrderType,
+    PositionSide, PriceMatch, SelfTradePreventionMode, Symbol, SymbolFilter, TimeInForce,
+    WorkingType,
+};
+
+pub mod request;
+pub mod response;
@@ -0,0 +1,13 @@
+use serde::Serialize;
+
+// the quantity of coin-margined orders is the number of contracts
+pub use crate::usdm_futures::types::request::{
+    AllOrders, KlineCandlestickData, NewOrder, OptionalSymbol, OrderId,
+};
+
+/// Optional pair, coin-margined market data are queried by pair or symbol
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionalPair {
+    pub pair: Option<String>,
+}
@@ -0,0 +1,116 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use super::{
+    ContractStatus, ContractType, MarginType, OrderSide, OrderStatus, OrderType, PositionSide,
+    SymbolFilter, TimeInForce, WorkingType,
+};
+
+pub use crate::usdm_futures::types::response::{KlineCandlestickData, ServerTime};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeInfo {
+    pub timezone: String,
+    pub server_time: i64,
+    pub rate_limits: Vec<serde_json::Value>,
+    pub exchange_filters: Vec<serde_json::Value>,
+    pub symbols: Vec<ExchangeInfoSymbol>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeInfoSymbol {
+    pub symbol: String,
+    pub pair: String,
+    pub contract_type: ContractType,
+    pub delivery_date: i64,
+    pub onboard_date: i64,
+    pub contract_status: ContractStatus,
+    /// Value of one contract in quote asset, e.g. 100 USD for BTCUSD
+    pub contract_size: Decimal,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub margin_asset: String,
+    pub price_precision: i64,
+    pub quantity_precision: i64,
+    pub base_asset_precision: i64,
+    pub quote_precision: i64,
+    pub filters: Vec<SymbolFilter>,
+    pub order_types: Vec<OrderType>,
+    pub time_in_force: Vec<TimeInForce>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TickerPrice {
+    pub symbol: String,
+    pub ps: String,
+    pub price: Decimal,
+    pub time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderInfo {
+    pub avg_price: Decimal,
+    pub client_order_id: String,
+    /// Executed value in base asset
+    pub cum_base: Decimal,
+    pub executed_qty: Decimal,
+    pub order_id: u64,
+    pub orig_qty: Decimal,
+    pub orig_type: OrderType,
+    pub price: Decimal,
+    pub reduce_only: bool,
+    pub side: OrderSide,
+    pub position_side: PositionSide,
+    pub status: OrderStatus,
+    pub stop_price: Decimal,
+    pub close_position: bool,
+    pub symbol: String,
+    pub pair: String,
+    pub time: Option<i64>,
+    pub time_in_force: TimeInForce,
+    #[serde(rename = "type")]
+    pub order_type: OrderType,
+    pub activate_price: Option<Decimal>,
+    pub price_rate: Option<Decimal>,
+    pub update_time: i64,
+    pub working_type: WorkingType,
+    pub price_protect: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionInformation {
+    pub symbol: String,
+    pub position_amt: Decimal,
+    pub entry_price: Decimal,
+    pub break_even_price: Decimal,
+    pub mark_price: Decimal,
+    pub un_realized_profit: Decimal,
+    pub liquidation_price: Decimal,
+    pub leverage: Decimal,
+    pub max_qty: Decimal,
+    pub margin_type: MarginType,
+    pub isolated_margin: Decimal,
+    pub is_auto_add_margin: String,
+    pub position_side: PositionSide,
+    pub notional_value: Decimal,
+    pub isolated_wallet: Decimal,
+    pub update_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBalance {
+    pub account_alias: String,
+    pub asset: String,
+    pub balance: Decimal,
+    pub withdraw_available: Decimal,
+    pub cross_wallet_balance: Decimal,
+    pub cross_un_pnl: Decimal,
+    pub available_balance: Decimal,
+    pub update_time: i64,
+}
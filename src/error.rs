@@ -14,6 +14,27 @@ pub enum Error {
     #[error("{0}")]
     Serde(String),
 
+    /// Error response of binance api, `code` is the error code in body if it's json, and `msg`
+    /// is the message in body, or the whole body if it's not json
+    #[error("binance api error, http code: {http_status}, code: {code:?}, message: {msg}")]
+    Binance {
+        http_status: u16,
+        code: Option<i64>,
+        msg: String,
+    },
+
+    /// Error of an order in a batch, while the batch request itself succeeded
+    #[error("binance batch order error, code: {code}, message: {msg}")]
+    BatchOrder { code: i64, msg: String },
+
+    /// Error response of a websocket command, e.g. subscribing to an invalid stream name
+    #[error("websocket response error, id: {id}, code: {code}, message: {msg}")]
+    WsResponse { id: u64, code: u64, msg: String },
+
+    /// Websocket connection closed by server with a close frame
+    #[error("connection closed by server, code: {code}, reason: {reason}")]
+    WsClosed { code: u16, reason: String },
+
     #[error(transparent)]
     StdIo(#[from] std::io::Error),
 
@@ -35,6 +56,9 @@ pub enum Error {
     #[error(transparent)]
     RustDecimal(#[from] rust_decimal::Error),
 
+    #[error(transparent)]
+    NativeTls(#[from] tokio_native_tls::native_tls::Error),
+
     #[error(transparent)]
     Tungstenite(#[from] tokio_tungstenite::tungstenite::Error),
 
@@ -46,10 +70,22 @@ pub enum Error {
 
     #[error(transparent)]
     FuturesChannel(#[from] futures_channel::mpsc::TrySendError<response::Stream>),
+
+    #[error(transparent)]
+    FuturesChannelSend(#[from] futures_channel::mpsc::SendError),
 }
 
 impl Error {
     pub fn new(message: &str) -> Self {
         Error::Message(message.to_string())
     }
+
+    /// Http status code of the failed response
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            Error::Binance { http_status, .. } => Some(*http_status),
+            Error::Reqwest(e) => e.status().map(|i| i.as_u16()),
+            _ => None,
+        }
+    }
 }
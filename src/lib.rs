@@ -1,3 +1,53 @@
+/// Implement `Display` and `FromStr` of an enum by the serde name of its variants, the fallback
+/// variant of unknown values, if any, is rejected by `FromStr`
+macro_rules! impl_enum_str {
+    (@display $t:ty) => {
+        impl std::fmt::Display for $t {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let s = serde_json::to_string(self).unwrap_or_default();
+                let s = s.trim_matches('"');
+                write!(f, "{s}")
+            }
+        }
+    };
+    ($t:ty) => {
+        impl_enum_str!(@display $t);
+
+        impl std::str::FromStr for $t {
+            type Err = $crate::error::Error;
+
+            fn from_str(s: &str) -> $crate::error::Result<Self> {
+                let s = format!("\"{s}\"");
+                Ok(serde_json::from_str(&s)?)
+            }
+        }
+    };
+    ($t:ty, $unknown:path) => {
+        impl_enum_str!(@display $t);
+
+        impl std::str::FromStr for $t {
+            type Err = $crate::error::Error;
+
+            fn from_str(s: &str) -> $crate::error::Result<Self> {
+                match serde_json::from_str::<Self>(&format!("\"{s}\""))? {
+                    $unknown => Err($crate::error::Error::new(&format!(
+                        "unknown {}: {s}",
+                        stringify!($t)
+                    ))),
+                    v => Ok(v),
+                }
+            }
+        }
+    };
+}
+
+pub mod coinm_futures;
 pub mod error;
+mod log;
+pub mod query;
+mod rest;
 pub mod spot;
+mod stream;
+pub mod timestamp;
 pub mod usdm_futures;
+pub mod util;
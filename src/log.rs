@@ -0,0 +1,15 @@
+//! Logging macros of the crate, forwarded to `tracing` or compiled to no-ops without the
+//! `tracing` feature. The arguments are still type checked so call sites are the same.
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{debug, error, info, warn};
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! noop {
+    ($($arg:tt)*) => {{
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use {noop as debug, noop as error, noop as info, noop as warn};
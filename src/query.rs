@@ -0,0 +1,40 @@
+//! Extra query parameters of requests, e.g. parameters which are not in the request types yet
+//!
+//! ```ignore
+//! use binance_client::query::QueryExt;
+//!
+//! let params = OptionalSymbol { symbol: None }.with_query("recvWindow", 10000);
+//! ```
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use serde::Serialize;
+
+/// Request parameters with extra query pairs which are appended after the parameters
+#[derive(Debug, Clone, Serialize)]
+pub struct WithQuery<T> {
+    #[serde(flatten)]
+    params: T,
+    #[serde(flatten)]
+    extra: BTreeMap<String, String>,
+}
+
+impl<T> WithQuery<T> {
+    pub fn with_query(mut self, key: &str, value: impl Display) -> Self {
+        self.extra.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+pub trait QueryExt: Serialize + Sized {
+    /// Append a query pair to the parameters
+    fn with_query(self, key: &str, value: impl Display) -> WithQuery<Self> {
+        WithQuery {
+            params: self,
+            extra: BTreeMap::new(),
+        }
+        .with_query(key, value)
+    }
+}
+
+impl<T: Serialize> QueryExt for T {}
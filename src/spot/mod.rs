@@ -1 +1,3 @@
-// pub mod stream;
+pub mod api;
+pub mod stream;
+pub mod types;
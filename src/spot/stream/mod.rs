@@ -1,14 +1,18 @@
-use futures_channel::mpsc::UnboundedSender;
-use futures_util::{SinkExt, StreamExt};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
-use tracing::{error, info};
+use std::ops::ControlFlow;
 
-use crate::error::Result;
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    error::{Error, Result},
+    log::error,
+    stream::{Exit, IDLE_TIMEOUT, SentCommands, close_error, connect, drive, idle_error},
+};
 
 mod request;
 mod response;
 
-pub use request::{Command, CommandMethod, CommandParam};
+pub use request::{Command, CommandMethod, CommandParam, CommandParamKind};
 pub use response::{Response, StreamItem};
 
 // const URL: &str = "wss://stream.binance.com/stream";
@@ -18,27 +22,49 @@ pub async fn receive(
     params: Vec<CommandParam>,
     tx: UnboundedSender<(String, Vec<StreamItem>)>,
 ) -> Result<()> {
-    let (mut stream, _) = connect_async(URL).await?;
-    let subscribe_msg = Command::new(CommandMethod::Subscribe, &params, 0);
-    stream.send(subscribe_msg.to_message()?).await?;
-    while let Some(msg) = stream.next().await {
-        match msg? {
-            Message::Text(msg) => match serde_json::from_str(&msg)? {
-                Response::Error { error, id } => {
-                    error!(
-                        "error, id: {id}, code: {}, message: {}",
-                        error.code, error.msg
-                    );
-                    break;
-                }
-                Response::Result { result, id } => {
-                    info!("result: {result:?}, id: {id}");
-                }
-                Response::Stream { stream, data } => tx.unbounded_send((stream, data))?,
-            },
-            Message::Ping(payload) => stream.send(Message::Pong(payload)).await?,
-            x => error!("invalid message from server: {x:?}"),
+    let (_commands_tx, commands) = futures_channel::mpsc::unbounded();
+    receive_with_commands(params, tx, commands).await
+}
+
+/// Same as `receive`, and send the commands from the channel to change subscriptions without
+/// reconnecting. The results of commands are logged with their ids.
+pub async fn receive_with_commands(
+    params: Vec<CommandParam>,
+    tx: UnboundedSender<(String, Vec<StreamItem>)>,
+    commands: UnboundedReceiver<Command>,
+) -> Result<()> {
+    let mut stream = connect(URL, None).await?;
+    let mut sent = SentCommands::default();
+    sent.send(
+        &mut stream,
+        &Command::new(CommandMethod::Subscribe, &params),
+    )
+    .await?;
+    let exit = drive::<Response, _, _, _>(
+        &mut stream,
+        &CancellationToken::new(),
+        Some(IDLE_TIMEOUT),
+        &mut sent,
+        commands,
+        async |name, items| {
+            tx.unbounded_send((name.unwrap_or_default(), items))
+                .map_err(|e| Error::new(&e.to_string()))?;
+            Ok(ControlFlow::Continue(()))
+        },
+    )
+    .await?;
+    match exit {
+        Exit::Error {
+            code,
+            msg,
+            id,
+            command,
+        } => {
+            error!("error, id: {id}, code: {code}, message: {msg}, command: {command:?}");
+            Ok(())
         }
+        Exit::Closed(Some(frame)) => Err(close_error(&frame)),
+        Exit::Idle(timeout) => Err(idle_error(timeout)),
+        Exit::Closed(None) | Exit::Stopped | Exit::Shutdown => Ok(()),
     }
-    Ok(())
 }
@@ -1,26 +1,35 @@
 use serde::Serialize;
 use tokio_tungstenite::tungstenite::Message;
 
-use crate::error::Result;
+use crate::{error::Result, spot::types::KlineInterval, stream::StreamCommand};
 
+/// Command of a connection, its id is assigned by the connection when it's sent
 #[derive(Debug, Serialize)]
 pub struct Command {
     method: CommandMethod,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     params: Vec<String>,
-    id: u64,
 }
 
 impl Command {
-    pub fn new(method: CommandMethod, params: &[CommandParam], id: u64) -> Command {
+    pub fn new(method: CommandMethod, params: &[CommandParam]) -> Command {
         Command {
             method,
             params: params.iter().map(|i| i.serialize()).collect(),
-            id,
         }
     }
+}
 
-    pub fn to_message(&self) -> Result<Message> {
-        Ok(Message::Text(serde_json::to_string(&self)?.into()))
+impl StreamCommand for Command {
+    fn to_message(&self, id: u64) -> Result<Message> {
+        #[derive(Serialize)]
+        struct CommandMessage<'a> {
+            #[serde(flatten)]
+            command: &'a Command,
+            id: u64,
+        }
+        let message = CommandMessage { command: self, id };
+        Ok(Message::Text(serde_json::to_string(&message)?.into()))
     }
 }
 
@@ -28,27 +37,46 @@ impl Command {
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum CommandMethod {
     Subscribe,
-    // Unsubscribe,
-    // ListSubscriptions,
-    // SetProperty,
-    // GetProperty,
+    Unsubscribe,
+    ListSubscriptions,
+    SetProperty,
+    GetProperty,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CommandParam {
     kind: CommandParamKind,
-    interval: u64,
+    interval: Option<u64>,
 }
 
 impl CommandParam {
+    /// Stream with update speed in milliseconds
     pub fn new(kind: CommandParamKind, interval: u64) -> Self {
-        CommandParam { kind, interval }
+        CommandParam {
+            kind,
+            interval: Some(interval),
+        }
     }
 
     fn serialize(&self) -> String {
-        format!("{}@{}ms", self.kind.serialize(), self.interval)
+        match self.interval {
+            Some(interval) => format!("{}@{interval}ms", self.kind.serialize()),
+            None => self.kind.serialize(),
+        }
     }
 }
 
+/// Stream without update speed
+impl From<CommandParamKind> for CommandParam {
+    fn from(kind: CommandParamKind) -> Self {
+        CommandParam {
+            kind,
+            interval: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CommandParamKind {
     MiniTicker(String),
     Ticker {
@@ -56,6 +84,12 @@ pub enum CommandParamKind {
         /// 1h,4h,1d
         period: String,
     },
+    Kline {
+        symbol: String,
+        interval: KlineInterval,
+    },
+    Trade(String),
+    AggTrade(String),
 }
 
 impl CommandParamKind {
@@ -72,6 +106,11 @@ impl CommandParamKind {
             CommandParamKind::Ticker { symbol, period } => {
                 symbol_join(symbol, &format!("ticker_{period}"))
             }
+            CommandParamKind::Kline { symbol, interval } => {
+                format!("{}@kline_{interval}", symbol.to_lowercase())
+            }
+            CommandParamKind::Trade(symbol) => format!("{}@trade", symbol.to_lowercase()),
+            CommandParamKind::AggTrade(symbol) => format!("{}@aggTrade", symbol.to_lowercase()),
         }
     }
 }
@@ -1,13 +1,11 @@
-use chrono::{
-    serde::{ts_milliseconds, ts_milliseconds_option},
-    DateTime, Utc,
-};
-use rust_decimal::{
-    serde::{str as de_decimal, str_option as de_decimal_opt},
-    Decimal,
-};
+use rust_decimal::{Decimal, serde::str as de_decimal};
 use serde::Deserialize;
 
+use crate::{
+    stream::{Decode, Frame},
+    timestamp,
+};
+
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum Response {
@@ -16,13 +14,41 @@ pub enum Response {
         id: u64,
     },
     Result {
-        result: Option<String>,
+        /// `null` for subscription changes, list of streams for `LIST_SUBSCRIPTIONS`
+        result: Option<serde_json::Value>,
         id: u64,
     },
     Stream {
         stream: String,
         data: Vec<StreamItem>,
     },
+    Single {
+        stream: String,
+        data: Box<StreamItem>,
+    },
+}
+
+impl Decode for Response {
+    type Item = StreamItem;
+
+    fn into_frame(self) -> Frame<StreamItem> {
+        match self {
+            Response::Error { error, id } => Frame::Error {
+                code: error.code,
+                msg: error.msg,
+                id,
+            },
+            Response::Result { result, id } => Frame::Result { result, id },
+            Response::Stream { stream, data } => Frame::Items {
+                stream: Some(stream),
+                items: data,
+            },
+            Response::Single { stream, data } => Frame::Items {
+                stream: Some(stream),
+                items: vec![*data],
+            },
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,39 +58,252 @@ pub struct ErrorDetail {
 }
 
 #[derive(Debug, Deserialize)]
-pub struct StreamItem {
-    #[serde(rename = "e")]
-    pub event_type: String,
-    #[serde(rename = "E", with = "ts_milliseconds")]
-    pub event_time: DateTime<Utc>,
-    #[serde(rename = "s")]
-    pub symbol: String,
-    #[serde(rename = "c", with = "de_decimal")]
-    pub close_price: Decimal,
+#[serde(tag = "e")]
+pub enum StreamItem {
+    /// Individual Symbol Mini Ticker
+    #[serde(rename = "24hrMiniTicker")]
+    MiniTicker {
+        /// Event time
+        #[serde(rename = "E")]
+        event_time: i64,
+        /// Symbol
+        #[serde(rename = "s")]
+        symbol: String,
+        /// Close price
+        #[serde(rename = "c", with = "de_decimal")]
+        close_price: Decimal,
+        /// Open price
+        #[serde(rename = "o", with = "de_decimal")]
+        open_price: Decimal,
+        /// High price
+        #[serde(rename = "h", with = "de_decimal")]
+        high_price: Decimal,
+        /// Low price
+        #[serde(rename = "l", with = "de_decimal")]
+        low_price: Decimal,
+        /// Total traded base asset volume
+        #[serde(rename = "v", with = "de_decimal")]
+        total_traded_base_asset_volume: Decimal,
+        /// Total traded quote asset volume
+        #[serde(rename = "q", with = "de_decimal")]
+        total_traded_quote_asset_volume: Decimal,
+    },
+    /// Individual Symbol Rolling Window Statistics, the event type is `1hTicker`, `4hTicker`
+    /// or `1dTicker` by the window size.
+    #[serde(rename = "1hTicker", alias = "4hTicker", alias = "1dTicker")]
+    Ticker {
+        /// Event time
+        #[serde(rename = "E")]
+        event_time: i64,
+        /// Symbol
+        #[serde(rename = "s")]
+        symbol: String,
+        /// Price change
+        #[serde(rename = "p", with = "de_decimal")]
+        price_change: Decimal,
+        /// Price change percent
+        #[serde(rename = "P", with = "de_decimal")]
+        price_change_percent: Decimal,
+        /// Weighted average price
+        #[serde(rename = "w", with = "de_decimal")]
+        weighted_average_price: Decimal,
+        /// Close price
+        #[serde(rename = "c", with = "de_decimal")]
+        close_price: Decimal,
+        /// Open price
+        #[serde(rename = "o", with = "de_decimal")]
+        open_price: Decimal,
+        /// High price
+        #[serde(rename = "h", with = "de_decimal")]
+        high_price: Decimal,
+        /// Low price
+        #[serde(rename = "l", with = "de_decimal")]
+        low_price: Decimal,
+        /// Total traded base asset volume
+        #[serde(rename = "v", with = "de_decimal")]
+        total_traded_base_asset_volume: Decimal,
+        /// Total traded quote asset volume
+        #[serde(rename = "q", with = "de_decimal")]
+        total_traded_quote_asset_volume: Decimal,
+        /// Statistics open time
+        #[serde(rename = "O")]
+        statistics_open_time: i64,
+        /// Statistics close time
+        #[serde(rename = "C")]
+        statistics_close_time: i64,
+        /// First trade ID
+        #[serde(rename = "F")]
+        first_trade_id: u64,
+        /// Last trade Id
+        #[serde(rename = "L")]
+        last_trade_id: u64,
+        /// Total number of trades
+        #[serde(rename = "n")]
+        total_number_of_trades: u64,
+    },
+    #[serde(rename = "kline")]
+    Kline {
+        /// Event time
+        #[serde(rename = "E")]
+        event_time: i64,
+        /// Symbol
+        #[serde(rename = "s")]
+        symbol: String,
+        /// Kline
+        #[serde(rename = "k")]
+        kline: Kline,
+    },
+    #[serde(rename = "trade")]
+    Trade {
+        /// Event time
+        #[serde(rename = "E")]
+        event_time: i64,
+        /// Symbol
+        #[serde(rename = "s")]
+        symbol: String,
+        /// Trade ID
+        #[serde(rename = "t")]
+        trade_id: u64,
+        /// Price
+        #[serde(rename = "p", with = "de_decimal")]
+        price: Decimal,
+        /// Quantity
+        #[serde(rename = "q", with = "de_decimal")]
+        quantity: Decimal,
+        /// Trade time
+        #[serde(rename = "T")]
+        trade_time: i64,
+        /// Is the buyer the market maker?
+        #[serde(rename = "m")]
+        is_buyer_market_maker: bool,
+    },
+    #[serde(rename = "aggTrade")]
+    AggTrade {
+        /// Event time
+        #[serde(rename = "E")]
+        event_time: i64,
+        /// Symbol
+        #[serde(rename = "s")]
+        symbol: String,
+        /// Aggregate trade ID
+        #[serde(rename = "a")]
+        aggregate_trade_id: u64,
+        /// Price
+        #[serde(rename = "p", with = "de_decimal")]
+        price: Decimal,
+        /// Quantity
+        #[serde(rename = "q", with = "de_decimal")]
+        quantity: Decimal,
+        /// First trade ID
+        #[serde(rename = "f")]
+        first_trade_id: u64,
+        /// Last trade ID
+        #[serde(rename = "l")]
+        last_trade_id: u64,
+        /// Trade time
+        #[serde(rename = "T")]
+        trade_time: i64,
+        /// Is the buyer the market maker?
+        #[serde(rename = "m")]
+        is_buyer_market_maker: bool,
+    },
+    /// Events which are not known yet, kept as raw json
+    #[serde(untagged, deserialize_with = "unknown_event")]
+    Unknown(serde_json::Value),
+}
+
+/// Event types of the variants, events of them are not taken as unknown
+const EVENTS: &[&str] = &[
+    "24hrMiniTicker",
+    "1hTicker",
+    "4hTicker",
+    "1dTicker",
+    "kline",
+    "trade",
+    "aggTrade",
+];
+
+fn unknown_event<'de, D>(deserializer: D) -> Result<serde_json::Value, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    crate::stream::unknown_event(deserializer, EVENTS)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Kline {
+    /// Kline start time
+    #[serde(rename = "t")]
+    pub start_time: i64,
+    /// Kline close time
+    #[serde(rename = "T")]
+    pub close_time: i64,
+    /// Interval
+    #[serde(rename = "i")]
+    pub interval: String,
+    /// First trade ID
+    #[serde(rename = "f")]
+    pub first_trade_id: i64,
+    /// Last trade ID
+    #[serde(rename = "L")]
+    pub last_trade_id: i64,
+    /// Open price
     #[serde(rename = "o", with = "de_decimal")]
     pub open_price: Decimal,
+    /// Close price
+    #[serde(rename = "c", with = "de_decimal")]
+    pub close_price: Decimal,
+    /// High price
     #[serde(rename = "h", with = "de_decimal")]
     pub high_price: Decimal,
+    /// Low price
     #[serde(rename = "l", with = "de_decimal")]
     pub low_price: Decimal,
+    /// Base asset volume
     #[serde(rename = "v", with = "de_decimal")]
-    pub total_traded_base_asset_volume: Decimal,
-    #[serde(rename = "q", with = "de_decimal")]
-    pub total_traded_quote_asset_volume: Decimal,
-    #[serde(default, rename = "p", with = "de_decimal_opt")]
-    pub price_change: Option<Decimal>,
-    #[serde(default, rename = "P", with = "de_decimal_opt")]
-    pub price_change_percent: Option<Decimal>,
-    #[serde(default, rename = "w", with = "de_decimal_opt")]
-    pub weighted_average_price: Option<Decimal>,
-    #[serde(default, rename = "O", with = "ts_milliseconds_option")]
-    pub statistics_open_time: Option<DateTime<Utc>>,
-    #[serde(default, rename = "C", with = "ts_milliseconds_option")]
-    pub statistics_close_time: Option<DateTime<Utc>>,
-    #[serde(rename = "F")]
-    pub first_trade_id: Option<u64>,
-    #[serde(rename = "L")]
-    pub last_trade_id: Option<u64>,
+    pub volume: Decimal,
+    /// Number of trades
     #[serde(rename = "n")]
-    pub total_number_of_trades: Option<u64>,
+    pub number_of_trades: u64,
+    /// Is this kline closed?
+    #[serde(rename = "x")]
+    pub is_closed: bool,
+    /// Quote asset volume
+    #[serde(rename = "q", with = "de_decimal")]
+    pub quote_asset_volume: Decimal,
+    /// Taker buy base asset volume
+    #[serde(rename = "V", with = "de_decimal")]
+    pub taker_buy_volume: Decimal,
+    /// Taker buy quote asset volume
+    #[serde(rename = "Q", with = "de_decimal")]
+    pub taker_buy_quote_asset_volume: Decimal,
+}
+
+impl StreamItem {
+    pub fn symbol(&self) -> &str {
+        match self {
+            StreamItem::MiniTicker { symbol, .. }
+            | StreamItem::Ticker { symbol, .. }
+            | StreamItem::Kline { symbol, .. }
+            | StreamItem::Trade { symbol, .. }
+            | StreamItem::AggTrade { symbol, .. } => symbol,
+            StreamItem::Unknown(v) => v.get("s").and_then(|s| s.as_str()).unwrap_or_default(),
+        }
+    }
+
+    /// Event time of the item in milliseconds, or the current time if an unknown event carries no
+    /// event time
+    pub fn event_time(&self) -> i64 {
+        match self {
+            StreamItem::MiniTicker { event_time, .. }
+            | StreamItem::Ticker { event_time, .. }
+            | StreamItem::Kline { event_time, .. }
+            | StreamItem::Trade { event_time, .. }
+            | StreamItem::AggTrade { event_time, .. } => *event_time,
+            StreamItem::Unknown(v) => v
+                .get("E")
+                .and_then(|t| t.as_i64())
+                .unwrap_or_else(timestamp::now_millis),
+        }
+    }
 }
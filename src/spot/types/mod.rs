@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+pub use crate::usdm_futures::types::{KlineInterval, OrderSide, Symbol};
+
+pub mod request;
+pub mod response;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SymbolStatus {
+    PreTrading,
+    Trading,
+    PostTrading,
+    EndOfDay,
+    Halt,
+    AuctionMatch,
+    Break,
+    /// Values which are not known yet
+    #[serde(other)]
+    Unknown,
+}
+impl_enum_str!(SymbolStatus, SymbolStatus::Unknown);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OrderStatus {
+    New,
+    PendingNew,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    PendingCancel,
+    Rejected,
+    Expired,
+    ExpiredInMatch,
+    /// Values which are not known yet
+    #[serde(other)]
+    Unknown,
+}
+impl_enum_str!(OrderStatus, OrderStatus::Unknown);
+
+impl OrderStatus {
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::New | OrderStatus::PendingNew | OrderStatus::PartiallyFilled
+        )
+    }
+
+    /// The order will not change anymore
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Canceled
+                | OrderStatus::Rejected
+                | OrderStatus::Expired
+                | OrderStatus::ExpiredInMatch
+        )
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OrderType {
+    Limit,
+    #[default]
+    Market,
+    StopLoss,
+    StopLossLimit,
+    TakeProfit,
+    TakeProfitLimit,
+    LimitMaker,
+    /// Values which are not known yet
+    #[serde(other)]
+    Unknown,
+}
+impl_enum_str!(OrderType, OrderType::Unknown);
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TimeInForce {
+    #[default]
+    Gtc,
+    Ioc,
+    Fok,
+}
+impl_enum_str!(TimeInForce);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NewOrderRespType {
+    Ack,
+    Result,
+    Full,
+}
+impl_enum_str!(NewOrderRespType);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SelfTradePreventionMode {
+    None,
+    ExpireTaker,
+    ExpireMaker,
+    ExpireBoth,
+    Decrement,
+}
+impl_enum_str!(SelfTradePreventionMode);
@@ -0,0 +1,66 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use super::{NewOrderRespType, OrderSide, OrderType, SelfTradePreventionMode, Symbol, TimeInForce};
+use crate::error::Result;
+
+pub use crate::usdm_futures::types::request::{KlineCandlestickData, OptionalSymbol, OrderId};
+
+/// Symbols of `exchangeInfo`, sent as a json array like `["BTCUSDT","ETHUSDT"]`
+#[derive(Debug, Serialize)]
+pub struct ExchangeInfoSymbols {
+    symbols: String,
+}
+
+impl ExchangeInfoSymbols {
+    pub fn new(symbols: &[&str]) -> Result<Self> {
+        let symbols: Vec<Symbol> = symbols.iter().map(|s| Symbol::from(*s)).collect();
+        Ok(ExchangeInfoSymbols {
+            symbols: serde_json::to_string(&symbols)?,
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewOrder {
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    #[serde(rename = "type")]
+    pub order_type: OrderType,
+    pub time_in_force: Option<TimeInForce>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub quantity: Option<Decimal>,
+    /// Used with `MARKET` orders, the amount of quote asset to spend or receive
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub quote_order_qty: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub price: Option<Decimal>,
+    /// A unique id among open orders. Automatically generated if not sent.
+    pub new_client_order_id: Option<String>,
+    /// Used with `STOP_LOSS`, `STOP_LOSS_LIMIT`, `TAKE_PROFIT`, and `TAKE_PROFIT_LIMIT` orders.
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub stop_price: Option<Decimal>,
+    /// Used with `STOP_LOSS`, `STOP_LOSS_LIMIT`, `TAKE_PROFIT`, and `TAKE_PROFIT_LIMIT` orders, in BIPS.
+    pub trailing_delta: Option<i64>,
+    /// Used with `LIMIT`, `STOP_LOSS_LIMIT`, and `TAKE_PROFIT_LIMIT` to create an iceberg order.
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub iceberg_qty: Option<Decimal>,
+    /// Default as `ACK` for `MARKET` and `LIMIT` order types, other order types default as `FULL`.
+    pub new_order_resp_type: Option<NewOrderRespType>,
+    pub self_trade_prevention_mode: Option<SelfTradePreventionMode>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exchange_info_symbols() {
+        let req = ExchangeInfoSymbols::new(&["btcusdt", "ETHUSDT"]).unwrap();
+        assert_eq!(
+            serde_urlencoded::to_string(&req).unwrap(),
+            "symbols=%5B%22BTCUSDT%22%2C%22ETHUSDT%22%5D"
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::{OrderSide, OrderStatus, OrderType, SymbolStatus, TimeInForce};
+
+pub use crate::usdm_futures::types::response::KlineCandlestickData;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeInfo {
+    pub timezone: String,
+    pub server_time: i64,
+    pub rate_limits: Vec<serde_json::Value>,
+    pub exchange_filters: Vec<serde_json::Value>,
+    pub symbols: Vec<ExchangeInfoSymbol>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeInfoSymbol {
+    pub symbol: String,
+    pub status: SymbolStatus,
+    pub base_asset: String,
+    pub base_asset_precision: u32,
+    pub quote_asset: String,
+    pub quote_asset_precision: u32,
+    pub order_types: Vec<OrderType>,
+    pub iceberg_allowed: bool,
+    pub oco_allowed: bool,
+    pub is_spot_trading_allowed: bool,
+    pub is_margin_trading_allowed: bool,
+    pub filters: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum TickerPrice {
+    One(TickerPriceItem),
+    Many(Vec<TickerPriceItem>),
+}
+
+impl From<TickerPrice> for Vec<TickerPriceItem> {
+    fn from(value: TickerPrice) -> Self {
+        match value {
+            TickerPrice::One(v) => vec![v],
+            TickerPrice::Many(v) => v,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TickerPriceItem {
+    pub symbol: String,
+    pub price: Decimal,
+}
+
+/// New order response, fields other than ids are only returned with `RESULT` or `FULL`
+/// response type, `fills` is only returned with `FULL` response type.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewOrder {
+    pub symbol: String,
+    pub order_id: u64,
+    pub order_list_id: i64,
+    pub client_order_id: String,
+    pub transact_time: i64,
+    pub price: Option<Decimal>,
+    pub orig_qty: Option<Decimal>,
+    pub executed_qty: Option<Decimal>,
+    pub cummulative_quote_qty: Option<Decimal>,
+    pub status: Option<OrderStatus>,
+    pub time_in_force: Option<TimeInForce>,
+    #[serde(rename = "type")]
+    pub order_type: Option<OrderType>,
+    pub side: Option<OrderSide>,
+    #[serde(default)]
+    pub fills: Vec<Fill>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fill {
+    pub price: Decimal,
+    pub qty: Decimal,
+    pub commission: Decimal,
+    pub commission_asset: String,
+    pub trade_id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderInfo {
+    pub symbol: String,
+    pub order_id: u64,
+    pub order_list_id: i64,
+    pub client_order_id: String,
+    /// Only returned by cancel order
+    pub orig_client_order_id: Option<String>,
+    pub price: Decimal,
+    pub orig_qty: Decimal,
+    pub executed_qty: Decimal,
+    pub cummulative_quote_qty: Decimal,
+    pub status: OrderStatus,
+    pub time_in_force: TimeInForce,
+    #[serde(rename = "type")]
+    pub order_type: OrderType,
+    pub side: OrderSide,
+    pub stop_price: Option<Decimal>,
+    pub iceberg_qty: Option<Decimal>,
+    pub time: Option<i64>,
+    pub update_time: Option<i64>,
+    pub transact_time: Option<i64>,
+    pub is_working: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+    pub maker_commission: i64,
+    pub taker_commission: i64,
+    pub buyer_commission: i64,
+    pub seller_commission: i64,
+    pub can_trade: bool,
+    pub can_withdraw: bool,
+    pub can_deposit: bool,
+    pub update_time: i64,
+    pub account_type: String,
+    pub balances: Vec<Balance>,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Balance {
+    pub asset: String,
+    pub free: Decimal,
+    pub locked: Decimal,
+}
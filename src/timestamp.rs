@@ -0,0 +1,158 @@
+//! Timestamps of responses and stream items are milliseconds since the unix epoch, the same with
+//! any feature. They are converted to `chrono` by the `chrono` feature, and to `time` by the
+//! `time` feature, the features are additive.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Milliseconds of a day
+pub const DAY_MILLIS: i64 = 24 * 3600 * 1000;
+
+/// Current timestamp in milliseconds
+pub fn now_millis() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_millis() as i64,
+        Err(e) => -(e.duration().as_millis() as i64),
+    }
+}
+
+/// Start of the UTC day of a timestamp in milliseconds, days are 24 hours without DST in UTC
+pub fn utc_day(millis: i64) -> i64 {
+    millis.div_euclid(DAY_MILLIS) * DAY_MILLIS
+}
+
+/// Time from `earlier` to `later` in milliseconds, zero if `later` is before `earlier`
+pub fn duration_between(earlier: i64, later: i64) -> Duration {
+    Duration::from_millis(later.saturating_sub(earlier).max(0) as u64)
+}
+
+/// Conversions of timestamps to `chrono::DateTime<Utc>`
+#[cfg(feature = "chrono")]
+pub mod chrono {
+    pub type DateTime = ::chrono::DateTime<::chrono::Utc>;
+
+    pub fn from_millis(millis: i64) -> Option<DateTime> {
+        DateTime::from_timestamp_millis(millis)
+    }
+
+    pub fn to_millis(time: &DateTime) -> i64 {
+        time.timestamp_millis()
+    }
+
+    /// UTC date of a timestamp in milliseconds
+    pub fn utc_date(millis: i64) -> Option<::chrono::NaiveDate> {
+        from_millis(millis).map(|i| i.date_naive())
+    }
+
+    /// Serialize and deserialize `DateTime` as a timestamp in milliseconds, used by
+    /// `#[serde(with = "binance_client::timestamp::chrono::ts_milliseconds")]`
+    pub mod ts_milliseconds {
+        use serde::{Deserialize, Deserializer, Serializer, de};
+
+        use super::{DateTime, from_millis, to_millis};
+
+        pub fn serialize<S>(time: &DateTime, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_i64(to_millis(time))
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let millis = i64::deserialize(deserializer)?;
+            from_millis(millis)
+                .ok_or_else(|| de::Error::custom(format!("invalid timestamp: {millis}")))
+        }
+    }
+}
+
+/// Conversions of timestamps to `time::OffsetDateTime` in UTC
+#[cfg(feature = "time")]
+pub mod time {
+    pub type DateTime = ::time::OffsetDateTime;
+
+    pub fn from_millis(millis: i64) -> Option<DateTime> {
+        DateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000).ok()
+    }
+
+    pub fn to_millis(time: &DateTime) -> i64 {
+        (time.unix_timestamp_nanos() / 1_000_000) as i64
+    }
+
+    /// Serialize and deserialize `DateTime` as a timestamp in milliseconds, used by
+    /// `#[serde(with = "binance_client::timestamp::time::ts_milliseconds")]`
+    pub mod ts_milliseconds {
+        use serde::{Deserialize, Deserializer, Serializer, de};
+
+        use super::{DateTime, from_millis, to_millis};
+
+        pub fn serialize<S>(time: &DateTime, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_i64(to_millis(time))
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let millis = i64::deserialize(deserializer)?;
+            from_millis(millis)
+                .ok_or_else(|| de::Error::custom(format!("invalid timestamp: {millis}")))
+        }
+    }
+}
+
+/// Bridge of the timestamps in milliseconds, e.g. `order.update_time` and `item.event_time()`,
+/// to the date time types of the enabled features
+pub trait AsDateTime {
+    /// `None` if the timestamp is out of range
+    #[cfg(feature = "chrono")]
+    fn as_chrono(&self) -> Option<self::chrono::DateTime>;
+
+    /// `None` if the timestamp is out of range
+    #[cfg(feature = "time")]
+    fn as_time(&self) -> Option<self::time::DateTime>;
+}
+
+impl AsDateTime for i64 {
+    #[cfg(feature = "chrono")]
+    fn as_chrono(&self) -> Option<self::chrono::DateTime> {
+        self::chrono::from_millis(*self)
+    }
+
+    #[cfg(feature = "time")]
+    fn as_time(&self) -> Option<self::time::DateTime> {
+        self::time::from_millis(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utc_days() {
+        // 2024-03-10T23:59:59.999Z and 2024-03-11T00:00:00Z
+        assert_eq!(utc_day(1710115199999), 1710028800000);
+        assert_eq!(utc_day(1710115200000), 1710115200000);
+        assert_eq!(utc_day(-1), -DAY_MILLIS);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn utc_dates() {
+        let date = |y, m, d| ::chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            super::chrono::utc_date(1710115199999),
+            Some(date(2024, 3, 10))
+        );
+        assert_eq!(
+            super::chrono::utc_date(1710115200000),
+            Some(date(2024, 3, 11))
+        );
+    }
+}
@@ -1,15 +1,70 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+use futures_util::{Stream, TryStreamExt, stream};
 use rust_decimal::Decimal;
 use serde::Deserialize;
 
 use crate::{
     error::{Error, Result},
+    log::warn,
+    timestamp,
     usdm_futures::{
         api::Client,
-        types::{self, OrderSide, OrderStatus, OrderType, TimeInForce, request::OrderId},
+        types::{
+            self, IncomeType, OrderSide, OrderStatus, OrderType, PositionSide, PriceMatch,
+            SymbolFilter, TimeInForce, WorkingType, request::OrderId,
+        },
     },
 };
 
-pub struct ExtendClient<'a>(&'a Client);
+/// Max time window in milliseconds of `allOrders` and `userTrades`
+const HISTORY_WINDOW: i64 = 7 * 24 * 3600 * 1000;
+/// Max limit of `allOrders` and `userTrades`
+const HISTORY_LIMIT: usize = 1000;
+/// Max limit of `klines`
+const KLINE_LIMIT: i64 = 1500;
+/// Query times to confirm an order is canceled
+const CANCEL_CONFIRM_TIMES: u32 = 5;
+const CANCEL_CONFIRM_INTERVAL: Duration = Duration::from_millis(200);
+/// Default interval of polling order status in `wait_for_fill`
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Max length of client order ids
+const CLIENT_ORDER_ID_MAX_LEN: usize = 36;
+/// Error code of querying an order which doesn't exist
+const NO_SUCH_ORDER: i64 = -2013;
+/// Error code of querying an algo order which doesn't exist
+const NO_SUCH_ALGO_ORDER: i64 = -20130;
+/// Max query times of an order of unknown execution status before it's taken as not placed
+const ORDER_LOOKUP_TIMES: u32 = 5;
+/// Max time of querying an order of unknown execution status, an order may become visible to
+/// queries a while after it's placed
+const ORDER_LOOKUP_WINDOW: Duration = Duration::from_secs(10);
+/// Length of generated ulid client order ids
+const ULID_LEN: usize = 26;
+/// Default time to live of the cached exchange info
+const EXCHANGE_INFO_TTL: Duration = Duration::from_secs(3600);
+
+pub struct ExtendClient<'a> {
+    client: &'a Client,
+    auto_round: bool,
+    auto_position_side: bool,
+    hedge_mode: RwLock<Option<bool>>,
+    exchange_info: RwLock<Option<ExchangeInfoCache>>,
+    exchange_info_ttl: Duration,
+    poll_interval: Duration,
+    id_generator: Option<Box<dyn Fn() -> String + Send + Sync + 'a>>,
+    order_retries: u32,
+    position_side: Option<PositionSide>,
+}
+
+struct ExchangeInfoCache {
+    updated_at: Instant,
+    symbols: HashMap<String, types::response::ExchangeInfoSymbol>,
+}
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type")]
@@ -20,13 +75,32 @@ pub enum NewOrder {
         quantity: Decimal,
         price: Decimal,
         time_in_force: TimeInForce,
+        /// Cancel time in milliseconds of `GTD` time in force
+        good_till_date: Option<i64>,
+        reduce_only: Option<bool>,
+        position_side: Option<PositionSide>,
+        /// Client order id, generated if not set
+        client_order_id: Option<String>,
+    },
+    /// `GTC` limit order priced by binance from the order book, e.g. for passive quoting
+    LimitMatch {
+        symbol: String,
+        side: types::OrderSide,
+        quantity: Decimal,
+        price_match: PriceMatch,
         reduce_only: Option<bool>,
+        position_side: Option<PositionSide>,
+        /// Client order id, generated if not set
+        client_order_id: Option<String>,
     },
     Market {
         symbol: String,
         side: types::OrderSide,
         quantity: Decimal,
         reduce_only: Option<bool>,
+        position_side: Option<PositionSide>,
+        /// Client order id, generated if not set
+        client_order_id: Option<String>,
     },
     StopLimit {
         symbol: String,
@@ -37,6 +111,11 @@ pub enum NewOrder {
         take_profit: bool,
         reduce_only: Option<bool>,
         price_protect: Option<bool>,
+        /// Price type of the trigger, `CONTRACT_PRICE` by binance if not set
+        working_type: Option<WorkingType>,
+        position_side: Option<PositionSide>,
+        /// Client order id, generated if not set
+        client_order_id: Option<String>,
     },
     StopMarket {
         symbol: String,
@@ -46,14 +125,269 @@ pub enum NewOrder {
         reduce_only: Option<bool>,
         close_position: Option<bool>,
         price_protect: Option<bool>,
+        /// Price type of the trigger, `CONTRACT_PRICE` by binance if not set
+        working_type: Option<WorkingType>,
+        position_side: Option<PositionSide>,
+        /// Client order id, generated if not set
+        client_order_id: Option<String>,
+    },
+    TrailingStopMarket {
+        symbol: String,
+        side: types::OrderSide,
+        quantity: Decimal,
+        /// Callback rate in percent, from 0.1 to 10
+        callback_rate: Decimal,
+        /// Price to activate the trailing, the latest price if not set
+        activation_price: Option<Decimal>,
+        reduce_only: Option<bool>,
+        /// Price type of the trigger, `CONTRACT_PRICE` by binance if not set
+        working_type: Option<WorkingType>,
+        position_side: Option<PositionSide>,
+        /// Client order id, generated if not set
+        client_order_id: Option<String>,
     },
 }
 
+fn new_client_order_id() -> String {
+    ulid::Ulid::new().to_string()
+}
+
+/// Time windows `[start_time, end_time]` in milliseconds of at most `KLINE_LIMIT` klines each,
+/// covering the klines opened in `[start, end)`
+fn kline_windows(interval: types::KlineInterval, start: i64, end: i64) -> Vec<(i64, i64)> {
+    let window = Duration::from(interval).as_millis() as i64 * KLINE_LIMIT;
+    let mut windows = Vec::new();
+    let mut start_time = interval.get_start_time(start);
+    while start_time < end {
+        let end_time = end.min(start_time + window);
+        windows.push((start_time, end_time - 1));
+        start_time = end_time;
+    }
+    windows
+}
+
+/// The request may have reached the matching engine without a response, a connection failed
+/// to establish is retried by the rest client already
+fn is_unknown_execution(e: &Error) -> bool {
+    match e {
+        Error::Reqwest(e) => !e.is_connect(),
+        Error::Binance { http_status, .. } => *http_status >= 500,
+        _ => false,
+    }
+}
+
+/// Check the rule of client order id: `^[\.A-Z\:/a-z0-9_-]{1,36}$`
+fn validate_client_order_id(id: &str) -> Result<()> {
+    let valid_char =
+        |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | ':' | '/' | '_' | '-');
+    if id.is_empty() || id.len() > CLIENT_ORDER_ID_MAX_LEN || !id.chars().all(valid_char) {
+        return Err(Error::new(&format!("invalid client order id: {id}")));
+    }
+    Ok(())
+}
+
+impl NewOrder {
+    pub fn symbol(&self) -> &str {
+        match self {
+            NewOrder::Limit { symbol, .. }
+            | NewOrder::LimitMatch { symbol, .. }
+            | NewOrder::Market { symbol, .. }
+            | NewOrder::StopLimit { symbol, .. }
+            | NewOrder::StopMarket { symbol, .. }
+            | NewOrder::TrailingStopMarket { symbol, .. } => symbol,
+        }
+    }
+
+    /// Client order id of the order if set, `ExtendClient::assign_client_order_id` generates it
+    /// before the order is placed
+    pub fn client_order_id(&self) -> Option<&str> {
+        match self {
+            NewOrder::Limit {
+                client_order_id, ..
+            }
+            | NewOrder::LimitMatch {
+                client_order_id, ..
+            }
+            | NewOrder::Market {
+                client_order_id, ..
+            }
+            | NewOrder::StopLimit {
+                client_order_id, ..
+            }
+            | NewOrder::StopMarket {
+                client_order_id, ..
+            }
+            | NewOrder::TrailingStopMarket {
+                client_order_id, ..
+            } => client_order_id.as_deref(),
+        }
+    }
+
+    fn client_order_id_mut(&mut self) -> &mut Option<String> {
+        match self {
+            NewOrder::Limit {
+                client_order_id, ..
+            }
+            | NewOrder::LimitMatch {
+                client_order_id, ..
+            }
+            | NewOrder::Market {
+                client_order_id, ..
+            }
+            | NewOrder::StopLimit {
+                client_order_id, ..
+            }
+            | NewOrder::StopMarket {
+                client_order_id, ..
+            }
+            | NewOrder::TrailingStopMarket {
+                client_order_id, ..
+            } => client_order_id,
+        }
+    }
+
+    /// Set the position side if it's not set. `reduceOnly` is rejected with `LONG` or `SHORT`,
+    /// so it's cleared, the order side closes the position instead.
+    pub fn set_position_side(&mut self, side: PositionSide) {
+        let (reduce_only, position_side) = match self {
+            NewOrder::Limit {
+                reduce_only,
+                position_side,
+                ..
+            }
+            | NewOrder::LimitMatch {
+                reduce_only,
+                position_side,
+                ..
+            }
+            | NewOrder::Market {
+                reduce_only,
+                position_side,
+                ..
+            }
+            | NewOrder::StopLimit {
+                reduce_only,
+                position_side,
+                ..
+            }
+            | NewOrder::StopMarket {
+                reduce_only,
+                position_side,
+                ..
+            }
+            | NewOrder::TrailingStopMarket {
+                reduce_only,
+                position_side,
+                ..
+            } => (reduce_only, position_side),
+        };
+        if position_side.is_some() {
+            return;
+        }
+        *position_side = Some(side);
+        if side != PositionSide::Both {
+            *reduce_only = None;
+        }
+    }
+
+    /// Set the position side in hedge mode if it's not set, an order closing a position is
+    /// sent to the opposite side. `reduceOnly` is rejected in hedge mode, so it's cleared.
+    pub fn fill_position_side(&mut self, hedge_mode: bool) {
+        if !hedge_mode {
+            return;
+        }
+        let (side, reduce_only, close_position, position_side) = match self {
+            NewOrder::Limit {
+                side,
+                reduce_only,
+                position_side,
+                ..
+            }
+            | NewOrder::LimitMatch {
+                side,
+                reduce_only,
+                position_side,
+                ..
+            }
+            | NewOrder::Market {
+                side,
+                reduce_only,
+                position_side,
+                ..
+            }
+            | NewOrder::StopLimit {
+                side,
+                reduce_only,
+                position_side,
+                ..
+            }
+            | NewOrder::TrailingStopMarket {
+                side,
+                reduce_only,
+                position_side,
+                ..
+            } => (*side, reduce_only, None, position_side),
+            NewOrder::StopMarket {
+                side,
+                reduce_only,
+                close_position,
+                position_side,
+                ..
+            } => (*side, reduce_only, *close_position, position_side),
+        };
+        let closing = reduce_only.take() == Some(true) || close_position == Some(true);
+        if position_side.is_none() {
+            *position_side = Some(match (side, closing) {
+                (OrderSide::Buy, false) | (OrderSide::Sell, true) => PositionSide::Long,
+                (OrderSide::Sell, false) | (OrderSide::Buy, true) => PositionSide::Short,
+            });
+        }
+    }
+
+    /// Round price and quantity to the filters of the symbol, and check the min notional
+    pub fn round(&mut self, rules: &types::response::ExchangeInfoSymbol) -> Result<()> {
+        match self {
+            NewOrder::Limit {
+                quantity, price, ..
+            } => {
+                *quantity = rules.round_qty(*quantity);
+                *price = rules.round_price(*price);
+                rules.validate_notional(*price, *quantity)?;
+            }
+            // priced by binance, but still a `LIMIT` order checked by `LOT_SIZE`
+            NewOrder::LimitMatch { quantity, .. } => *quantity = rules.round_qty(*quantity),
+            NewOrder::Market { quantity, .. } => *quantity = rules.round_market_qty(*quantity),
+            NewOrder::StopLimit {
+                quantity,
+                price,
+                stop_price,
+                ..
+            } => {
+                *quantity = rules.round_qty(*quantity);
+                *price = rules.round_price(*price);
+                *stop_price = rules.round_price(*stop_price);
+                rules.validate_notional(*price, *quantity)?;
+            }
+            NewOrder::StopMarket { stop_price, .. } => *stop_price = rules.round_price(*stop_price),
+            NewOrder::TrailingStopMarket {
+                quantity,
+                activation_price,
+                ..
+            } => {
+                *quantity = rules.round_qty(*quantity);
+                if let Some(price) = activation_price {
+                    *price = rules.round_price(*price);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl TryFrom<NewOrder> for types::request::NewOrder {
     type Error = Error;
 
     fn try_from(value: NewOrder) -> std::result::Result<Self, Self::Error> {
-        let new_client_order_id = ulid::Ulid::new().to_string();
         Ok(match value {
             NewOrder::Limit {
                 symbol,
@@ -61,30 +395,64 @@ impl TryFrom<NewOrder> for types::request::NewOrder {
                 quantity,
                 price,
                 time_in_force,
+                good_till_date,
                 reduce_only,
+                position_side,
+                client_order_id,
             } => types::request::NewOrder {
-                symbol,
+                symbol: symbol.into(),
                 side,
                 order_type: types::OrderType::Limit,
                 time_in_force: Some(time_in_force),
+                good_till_date,
                 quantity: Some(quantity),
                 price: Some(price),
-                new_client_order_id: Some(new_client_order_id),
+                new_client_order_id: Some(client_order_id.unwrap_or_else(new_client_order_id)),
                 reduce_only,
+                position_side,
                 ..Default::default()
             },
+            NewOrder::LimitMatch {
+                symbol,
+                side,
+                quantity,
+                price_match,
+                reduce_only,
+                position_side,
+                client_order_id,
+            } => {
+                if matches!(price_match, PriceMatch::None) {
+                    return Err(Error::new("price match is required for limit match order"));
+                }
+
+                types::request::NewOrder {
+                    symbol: symbol.into(),
+                    side,
+                    order_type: types::OrderType::Limit,
+                    time_in_force: Some(TimeInForce::Gtc),
+                    quantity: Some(quantity),
+                    price_match: Some(price_match),
+                    new_client_order_id: Some(client_order_id.unwrap_or_else(new_client_order_id)),
+                    reduce_only,
+                    position_side,
+                    ..Default::default()
+                }
+            }
             NewOrder::Market {
                 symbol,
                 side,
                 quantity,
                 reduce_only,
+                position_side,
+                client_order_id,
             } => types::request::NewOrder {
-                symbol,
+                symbol: symbol.into(),
                 side,
                 order_type: types::OrderType::Market,
                 quantity: Some(quantity),
-                new_client_order_id: Some(new_client_order_id),
+                new_client_order_id: Some(client_order_id.unwrap_or_else(new_client_order_id)),
                 reduce_only,
+                position_side,
                 ..Default::default()
             },
             NewOrder::StopLimit {
@@ -96,6 +464,9 @@ impl TryFrom<NewOrder> for types::request::NewOrder {
                 take_profit,
                 reduce_only,
                 price_protect,
+                working_type,
+                position_side,
+                client_order_id,
             } => {
                 let order_type = if take_profit {
                     types::OrderType::TakeProfit
@@ -104,19 +475,21 @@ impl TryFrom<NewOrder> for types::request::NewOrder {
                 };
 
                 types::request::NewOrder {
-                    symbol,
+                    symbol: symbol.into(),
                     side,
                     order_type,
                     quantity: Some(quantity),
                     price: Some(price),
                     stop_price: Some(stop_price),
-                    new_client_order_id: Some(new_client_order_id),
+                    new_client_order_id: Some(client_order_id.unwrap_or_else(new_client_order_id)),
                     reduce_only,
                     price_protect,
+                    working_type,
+                    position_side,
                     ..Default::default()
                 }
             }
-            NewOrder::StopMarket { .. } => {
+            NewOrder::StopMarket { .. } | NewOrder::TrailingStopMarket { .. } => {
                 return Err(Error::new("stop market type order use AlgoOrder instead"));
             }
         })
@@ -127,9 +500,11 @@ impl TryFrom<NewOrder> for types::request::NewAlgoOrder {
     type Error = Error;
 
     fn try_from(value: NewOrder) -> std::result::Result<Self, Self::Error> {
-        let new_client_order_id = ulid::Ulid::new().to_string();
         Ok(match value {
-            NewOrder::Limit { .. } | NewOrder::Market { .. } | NewOrder::StopLimit { .. } => {
+            NewOrder::Limit { .. }
+            | NewOrder::LimitMatch { .. }
+            | NewOrder::Market { .. }
+            | NewOrder::StopLimit { .. } => {
                 return Err(Error::new("limit type order use common new order instead"));
             }
             NewOrder::StopMarket {
@@ -140,6 +515,9 @@ impl TryFrom<NewOrder> for types::request::NewAlgoOrder {
                 mut reduce_only,
                 close_position,
                 price_protect,
+                working_type,
+                position_side,
+                client_order_id,
             } => {
                 let order_type = if take_profit {
                     types::OrderType::TakeProfitMarket
@@ -153,14 +531,48 @@ impl TryFrom<NewOrder> for types::request::NewAlgoOrder {
 
                 types::request::NewAlgoOrder {
                     algo_type: "CONDITIONAL".to_string(),
-                    symbol,
+                    symbol: symbol.into(),
                     side,
                     order_type,
                     trigger_price: Some(stop_price),
-                    client_algo_id: Some(new_client_order_id),
+                    client_algo_id: Some(client_order_id.unwrap_or_else(new_client_order_id)),
                     reduce_only,
                     close_position,
                     price_protect,
+                    working_type,
+                    position_side,
+                    ..Default::default()
+                }
+            }
+            NewOrder::TrailingStopMarket {
+                symbol,
+                side,
+                quantity,
+                callback_rate,
+                activation_price,
+                reduce_only,
+                working_type,
+                position_side,
+                client_order_id,
+            } => {
+                if !(Decimal::new(1, 1)..=Decimal::TEN).contains(&callback_rate) {
+                    return Err(Error::new(&format!(
+                        "callback rate {callback_rate} is out of range 0.1 to 10"
+                    )));
+                }
+
+                types::request::NewAlgoOrder {
+                    algo_type: "CONDITIONAL".to_string(),
+                    symbol: symbol.into(),
+                    side,
+                    order_type: types::OrderType::TrailingStopMarket,
+                    quantity: Some(quantity),
+                    callback_rate: Some(callback_rate),
+                    activation_price,
+                    client_algo_id: Some(client_order_id.unwrap_or_else(new_client_order_id)),
+                    reduce_only,
+                    working_type,
+                    position_side,
                     ..Default::default()
                 }
             }
@@ -261,29 +673,323 @@ impl From<types::response::AlgoOrderInfo> for NewOrderResult {
     }
 }
 
+/// Orders of `cancel_replace`
+#[derive(Debug)]
+pub struct CancelReplace {
+    /// The canceled order, or the filled order if it's filled before canceled. The new order
+    /// is placed as is, `executed_qty` of the canceled order is filled already.
+    pub old: types::response::OrderInfo,
+    /// The new order, `None` if the old order is filled before canceled
+    pub new: Option<types::response::OrderInfo>,
+}
+
+/// Account information with the derived margin figures
+#[derive(Debug)]
+pub struct AccountSummary {
+    /// Unrealized profit by asset
+    pub unrealized_profit: HashMap<String, Decimal>,
+    /// `totalMaintMargin / totalMarginBalance`, `None` if the margin balance is zero. The
+    /// account is liquidated when it reaches 1.
+    pub margin_ratio: Option<Decimal>,
+    /// `totalMarginBalance - totalInitialMargin`
+    pub free_margin: Decimal,
+    pub account: types::response::AccountInformationV3,
+}
+
+impl From<types::response::AccountInformationV3> for AccountSummary {
+    fn from(account: types::response::AccountInformationV3) -> Self {
+        let unrealized_profit = account
+            .assets
+            .iter()
+            .map(|i| (i.asset.clone(), i.unrealized_profit))
+            .collect();
+        let margin_ratio = account
+            .total_maint_margin
+            .checked_div(account.total_margin_balance);
+        let free_margin = account.total_margin_balance - account.total_initial_margin;
+        AccountSummary {
+            unrealized_profit,
+            margin_ratio,
+            free_margin,
+            account,
+        }
+    }
+}
+
+/// Walk `[start, end]` window by window, `key` returns the time and id of a row.
+///
+/// A full page continues from the time of its last row, rows at that time are returned again
+/// by binance and dropped by id.
+fn paginate<'a, T, F, Fut>(
+    start: i64,
+    end: i64,
+    fetch: F,
+    key: fn(&T) -> (i64, i64),
+) -> impl Stream<Item = Result<T>> + use<'a, T, F, Fut>
+where
+    T: 'a,
+    F: Fn(i64, i64) -> Fut + 'a,
+    Fut: Future<Output = Result<Vec<T>>> + 'a,
+{
+    stream::try_unfold(Some((start, HashSet::new())), move |state| {
+        let page = state.map(|(start, seen)| {
+            let window_end = end.min(start + HISTORY_WINDOW - 1);
+            (fetch(start, window_end), start, window_end, seen)
+        });
+        async move {
+            let Some((page, start, window_end, mut seen)) = page else {
+                return Ok::<_, Error>(None);
+            };
+            let rows = page.await?;
+            let last_time = match rows.last() {
+                Some(last) if rows.len() >= HISTORY_LIMIT => Some(key(last).0),
+                _ => None,
+            };
+            let boundary: Vec<i64> = match last_time {
+                Some(last_time) => rows
+                    .iter()
+                    .map(key)
+                    .filter(|(time, _)| *time == last_time)
+                    .map(|(_, id)| id)
+                    .collect(),
+                None => Vec::new(),
+            };
+            let rows: Vec<T> = rows
+                .into_iter()
+                .filter(|i| !seen.contains(&key(i).1))
+                .collect();
+
+            let next = match last_time {
+                // more than a page of rows in the same millisecond, skip the rest of them
+                Some(last_time) if last_time == start && rows.is_empty() => {
+                    Some((start + 1, HashSet::new()))
+                }
+                Some(last_time) => {
+                    if last_time != start {
+                        seen.clear();
+                    }
+                    seen.extend(boundary);
+                    Some((last_time, seen))
+                }
+                None if window_end < end => Some((window_end + 1, HashSet::new())),
+                None => None,
+            };
+            Ok(Some((rows, next)))
+        }
+    })
+    .map_ok(|rows| stream::iter(rows.into_iter().map(Ok::<T, Error>)))
+    .try_flatten()
+}
+
 impl<'a> ExtendClient<'a> {
     pub fn new(client: &'a Client) -> Self {
-        Self(client)
+        Self {
+            client,
+            auto_round: false,
+            auto_position_side: false,
+            hedge_mode: RwLock::new(None),
+            exchange_info: RwLock::new(None),
+            exchange_info_ttl: EXCHANGE_INFO_TTL,
+            poll_interval: POLL_INTERVAL,
+            id_generator: None,
+            order_retries: 0,
+            position_side: None,
+        }
+    }
+
+    /// Round price and quantity of new orders to the filters of the symbol
+    pub fn with_auto_round(mut self, auto_round: bool) -> Self {
+        self.auto_round = auto_round;
+        self
+    }
+
+    /// Set the position side of new orders by the position mode of the account
+    pub fn with_auto_position_side(mut self, auto_position_side: bool) -> Self {
+        self.auto_position_side = auto_position_side;
+        self
+    }
+
+    /// Position side of new orders which are not set, e.g. for a strategy trading one side in
+    /// hedge mode. It takes precedence over the auto position side.
+    pub fn with_position_side(mut self, position_side: PositionSide) -> Self {
+        self.position_side = Some(position_side);
+        self
+    }
+
+    /// Set the position mode of the account instead of querying it
+    pub fn with_hedge_mode(mut self, hedge_mode: bool) -> Self {
+        self.hedge_mode = RwLock::new(Some(hedge_mode));
+        self
+    }
+
+    /// Prefix generated client order ids, e.g. to attribute orders to a strategy. The prefix is
+    /// at most 10 characters since a ulid of 26 characters is appended.
+    pub fn with_id_prefix(self, prefix: &str) -> Result<Self> {
+        if prefix.len() + ULID_LEN > CLIENT_ORDER_ID_MAX_LEN {
+            return Err(Error::new(&format!(
+                "client order id prefix {prefix} is longer than {} characters",
+                CLIENT_ORDER_ID_MAX_LEN - ULID_LEN
+            )));
+        }
+        validate_client_order_id(&format!("{prefix}{}", new_client_order_id()))?;
+        let prefix = prefix.to_string();
+        Ok(self.with_id_generator(move || format!("{prefix}{}", new_client_order_id())))
+    }
+
+    /// Generate client order ids of new orders which are not set, instead of ulid
+    pub fn with_id_generator(mut self, generator: impl Fn() -> String + Send + Sync + 'a) -> Self {
+        self.id_generator = Some(Box::new(generator));
+        self
+    }
+
+    /// Interval of polling order status in `wait_for_fill`, default as 500 milliseconds
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Retry new orders of unknown execution status at most `retries` times, default as 0.
+    ///
+    /// If the request is sent but no response is received, or binance responds with a server
+    /// error, the order may be placed or not. The order is queried by its client order id
+    /// with a doubling interval from the poll interval, up to 5 times within 10 seconds, and
+    /// it's placed again only if none of the queries finds it. If a query fails otherwise, the
+    /// error of placing is returned without retrying.
+    pub fn with_order_retries(mut self, retries: u32) -> Self {
+        self.order_retries = retries;
+        self
+    }
+
+    /// Time to live of the cached exchange info, default as 1 hour
+    pub fn with_exchange_info_ttl(mut self, ttl: Duration) -> Self {
+        self.exchange_info_ttl = ttl;
+        self
+    }
+
+    /// Fetch exchange info and replace the cached one
+    pub async fn refresh(&self) -> Result<()> {
+        let exchange_info = self.client.exchange_info().await?;
+        let symbols = exchange_info
+            .symbols
+            .into_iter()
+            .map(|i| (i.symbol.clone(), i))
+            .collect();
+        *self
+            .exchange_info
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(ExchangeInfoCache {
+            updated_at: Instant::now(),
+            symbols,
+        });
+        Ok(())
+    }
+
+    async fn with_exchange_info<T, F>(&self, f: F) -> Result<T>
+    where
+        F: Fn(&ExchangeInfoCache) -> T,
+    {
+        {
+            let cache = self
+                .exchange_info
+                .read()
+                .unwrap_or_else(PoisonError::into_inner);
+            if let Some(cache) = cache.as_ref()
+                && cache.updated_at.elapsed() < self.exchange_info_ttl
+            {
+                return Ok(f(cache));
+            }
+        }
+        self.refresh().await?;
+        let cache = self
+            .exchange_info
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        cache
+            .as_ref()
+            .map(f)
+            .ok_or_else(|| Error::new("exchange info is not cached"))
+    }
+
+    /// Whether the account is in hedge mode, it's queried once and cached since the position
+    /// mode can only be changed without open positions and orders.
+    pub async fn hedge_mode(&self) -> Result<bool> {
+        if let Some(hedge_mode) = *self
+            .hedge_mode
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            return Ok(hedge_mode);
+        }
+        let hedge_mode = self
+            .client
+            .get_current_position_mode()
+            .await?
+            .dual_side_position;
+        *self
+            .hedge_mode
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(hedge_mode);
+        Ok(hedge_mode)
+    }
+
+    /// Symbol info from the cached exchange info
+    pub async fn symbol_info(
+        &self,
+        symbol: &str,
+    ) -> Result<Option<types::response::ExchangeInfoSymbol>> {
+        self.with_exchange_info(|i| i.symbols.get(symbol).cloned())
+            .await
+    }
+
+    /// Symbol filters from the cached exchange info
+    pub async fn filters(&self, symbol: &str) -> Result<Option<Vec<SymbolFilter>>> {
+        self.with_exchange_info(|i| i.symbols.get(symbol).map(|i| i.filters.clone()))
+            .await
     }
 
     pub async fn kline_candlestick_data(
         &self,
         params: types::request::KlineCandlestickData,
     ) -> Result<Vec<types::response::KlineCandlestickData>> {
-        let v = self.0.kline_candlestick_data(params).await?;
-        let mut result = Vec::new();
-        for item in v {
-            let item = item.try_into()?;
-            result.push(item);
+        self.client.klines_typed(params).await
+    }
+
+    /// Get klines opened in `[start, end)` in milliseconds, the time range is split into
+    /// requests of at most 1500 klines.
+    pub async fn klines_range(
+        &self,
+        symbol: &str,
+        interval: types::KlineInterval,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<types::response::KlineCandlestickData>> {
+        let mut result: Vec<types::response::KlineCandlestickData> = Vec::new();
+        for (start_time, end_time) in kline_windows(interval, start, end) {
+            let params = types::request::KlineCandlestickData {
+                symbol: symbol.into(),
+                interval,
+                start_time: Some(start_time),
+                end_time: Some(end_time),
+                limit: Some(KLINE_LIMIT),
+            };
+            let klines = self.kline_candlestick_data(params).await?;
+            let last_open_time = result.last().map(|i| i.open_time);
+            result.extend(
+                klines
+                    .into_iter()
+                    // the first window starts at the open time of the kline of `start`
+                    .filter(|i| i.open_time >= start)
+                    .filter(|i| last_open_time.is_none_or(|t| i.open_time > t)),
+            );
         }
         Ok(result)
     }
 
     pub async fn symbol_ticker_price(&self, symbol: &str) -> Result<Decimal> {
         let params = types::request::OptionalSymbol {
-            symbol: Some(symbol.to_string()),
+            symbol: Some(symbol.into()),
         };
-        let price = self.0.ticker_price(params).await?;
+        let price = self.client.ticker_price(params).await?;
         let price = match price {
             types::response::TickerPrice::One(v) => v.price,
             types::response::TickerPrice::Many(v) => {
@@ -297,29 +1003,604 @@ impl<'a> ExtendClient<'a> {
         Ok(price)
     }
 
+    /// Prices of symbols by a single request of all symbols, instead of a request per symbol.
+    /// Symbols which are not found are absent in the result.
+    pub async fn ticker_prices(&self, symbols: &[&str]) -> Result<HashMap<String, Decimal>> {
+        let symbols: HashSet<String> = symbols
+            .iter()
+            .map(|s| types::Symbol::from(*s).into_inner())
+            .collect();
+        let prices: Vec<types::response::TickerPriceItem> = self
+            .client
+            .ticker_price(types::request::OptionalSymbol { symbol: None })
+            .await?
+            .into();
+        Ok(prices
+            .into_iter()
+            .filter(|i| symbols.contains(i.symbol.as_str()))
+            .map(|i| (i.symbol, i.price))
+            .collect())
+    }
+
+    /// Apply auto round, auto position side and the id generator to a new order
+    async fn prepare(&self, mut params: NewOrder) -> Result<NewOrder> {
+        if self.auto_round {
+            let symbol = self
+                .symbol_info(params.symbol())
+                .await?
+                .ok_or_else(|| Error::new(&format!("symbol {} not found", params.symbol())))?;
+            params.round(&symbol)?;
+        }
+        if let Some(position_side) = self.position_side {
+            params.set_position_side(position_side);
+        }
+        if self.auto_position_side {
+            params.fill_position_side(self.hedge_mode().await?);
+        }
+        let client_order_id = params.client_order_id_mut();
+        if client_order_id.is_none() && self.id_generator.is_some() {
+            *client_order_id = Some(self.generate_client_order_id());
+        }
+        if let Some(id) = client_order_id {
+            validate_client_order_id(id)?;
+        }
+        Ok(params)
+    }
+
+    /// Client order id of the order, it's generated by the id generator and kept if not set, so
+    /// the order can be correlated before it's placed
+    pub fn assign_client_order_id<'p>(&self, params: &'p mut NewOrder) -> &'p str {
+        params
+            .client_order_id_mut()
+            .get_or_insert_with(|| self.generate_client_order_id())
+    }
+
+    fn generate_client_order_id(&self) -> String {
+        match &self.id_generator {
+            Some(generator) => generator(),
+            None => new_client_order_id(),
+        }
+    }
+
     pub async fn new_order(&self, params: NewOrder) -> Result<NewOrderResult> {
+        let mut params = self.prepare(params).await?;
+        if self.order_retries == 0 {
+            return self.place(params).await;
+        }
+        let client_order_id = self.assign_client_order_id(&mut params).to_string();
+        let mut attempt = 0;
+        loop {
+            let e = match self.place(params.clone()).await {
+                Err(e) if attempt < self.order_retries && is_unknown_execution(&e) => e,
+                result => return result,
+            };
+            attempt += 1;
+            warn!("execution status of order {client_order_id} is unknown: {e}, query it");
+            match self.lookup_order(&params, &client_order_id).await {
+                Ok(Some(order)) => return Ok(order),
+                Ok(None) => {}
+                // the order may be placed, retrying could place it twice
+                Err(_) => return Err(e),
+            }
+        }
+    }
+
+    /// Query an order of unknown execution status by its client order id, `None` if none of
+    /// the queries within `ORDER_LOOKUP_WINDOW` finds it
+    async fn lookup_order(
+        &self,
+        params: &NewOrder,
+        client_order_id: &str,
+    ) -> Result<Option<NewOrderResult>> {
+        let is_algo = matches!(
+            params,
+            NewOrder::StopMarket { .. } | NewOrder::TrailingStopMarket { .. }
+        );
+        let not_found = if is_algo {
+            NO_SUCH_ALGO_ORDER
+        } else {
+            NO_SUCH_ORDER
+        };
+        let deadline = Instant::now() + ORDER_LOOKUP_WINDOW;
+        let mut delay = self.poll_interval;
+        for _ in 0..ORDER_LOOKUP_TIMES {
+            tokio::time::sleep(delay).await;
+            let order_id = OrderId::new_client(params.symbol(), client_order_id);
+            match self.query_order(order_id, is_algo).await {
+                Ok(order) => return Ok(Some(order)),
+                Err(Error::Binance {
+                    code: Some(code), ..
+                }) if code == not_found => {}
+                Err(e) => return Err(e),
+            }
+            delay = (delay * 2).min(deadline.saturating_duration_since(Instant::now()));
+            if delay.is_zero() {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    async fn place(&self, params: NewOrder) -> Result<NewOrderResult> {
         Ok(match params {
-            NewOrder::Limit { .. } | NewOrder::Market { .. } | NewOrder::StopLimit { .. } => {
-                self.0.new_order(params.try_into()?).await?.into()
+            NewOrder::Limit { .. }
+            | NewOrder::LimitMatch { .. }
+            | NewOrder::Market { .. }
+            | NewOrder::StopLimit { .. } => self.client.new_order(params.try_into()?).await?.into(),
+            NewOrder::StopMarket { .. } | NewOrder::TrailingStopMarket { .. } => {
+                self.client.new_algo_order(params.try_into()?).await?.into()
             }
-            NewOrder::StopMarket { .. } => self.0.new_algo_order(params.try_into()?).await?.into(),
         })
     }
 
+    pub async fn account_summary(&self) -> Result<AccountSummary> {
+        Ok(self.client.account_information_v3().await?.into())
+    }
+
+    /// Positions with nonzero size of all symbols
+    pub async fn open_positions(&self) -> Result<Vec<types::response::PositionInformationV3>> {
+        let positions = self
+            .client
+            .position_information_v3(types::request::OptionalSymbol { symbol: None })
+            .await?;
+        Ok(positions
+            .into_iter()
+            .filter(|i| !i.position_amt.is_zero())
+            .collect())
+    }
+
+    /// Net position size of a symbol, positive for long and negative for short.
+    /// Long and short positions are summed in hedge mode, as the amount of short is negative.
+    pub async fn net_position(&self, symbol: &str) -> Result<Decimal> {
+        let positions = self
+            .client
+            .position_information_v3(types::request::OptionalSymbol {
+                symbol: Some(symbol.into()),
+            })
+            .await?;
+        Ok(positions.iter().map(|i| i.position_amt).sum())
+    }
+
+    /// Available balance of an asset, zero if the account has none
+    pub async fn asset_balance(&self, asset: &str) -> Result<Decimal> {
+        let balances = self.client.futures_account_balance_v3().await?;
+        Ok(balances
+            .into_iter()
+            .find(|i| i.asset == asset)
+            .map(|i| i.available_balance)
+            .unwrap_or_default())
+    }
+
+    /// Available balance of USDT
+    pub async fn usdt_balance(&self) -> Result<Decimal> {
+        self.asset_balance("USDT").await
+    }
+
+    /// Modify the isolated margin of a position, the position side is checked by the position
+    /// mode since binance rejects a mismatch with an obscure `-4061`
+    pub async fn modify_isolated_position_margin(
+        &self,
+        params: types::request::ModifyIsolatedPositionMargin,
+    ) -> Result<types::response::ModifyIsolatedPositionMargin> {
+        match (self.hedge_mode().await?, params.position_side) {
+            (true, None | Some(PositionSide::Both)) => {
+                return Err(Error::new(
+                    "position side LONG or SHORT is required in hedge mode",
+                ));
+            }
+            (false, Some(PositionSide::Long | PositionSide::Short)) => {
+                return Err(Error::new("position side must be BOTH in one-way mode"));
+            }
+            _ => {}
+        }
+        self.client.modify_isolated_position_margin(params).await
+    }
+
+    /// Validate a new order by binance without submitting it to the matching engine, the order
+    /// is prepared the same as `new_order`. Stop market and trailing stop market orders are
+    /// algo orders which can't be tested.
+    pub async fn test_order(&self, params: NewOrder) -> Result<()> {
+        let params = self.prepare(params).await?;
+        if let NewOrder::StopMarket { .. } | NewOrder::TrailingStopMarket { .. } = params {
+            return Err(Error::new("stop market order can not be tested"));
+        }
+        self.client.test_order(params.try_into()?).await?;
+        Ok(())
+    }
+
+    /// Close the position of the side at market, `None` is returned if the position is flat.
+    /// `reduceOnly` is only sent for `BOTH` side since it's rejected in hedge mode.
+    pub async fn close_position(
+        &self,
+        symbol: &str,
+        position_side: PositionSide,
+    ) -> Result<Option<types::response::OrderInfo>> {
+        let positions = self
+            .client
+            .position_information_v3(types::request::OptionalSymbol {
+                symbol: Some(symbol.into()),
+            })
+            .await?;
+        let Some(position) = positions
+            .into_iter()
+            .find(|i| i.position_side == position_side && !i.position_amt.is_zero())
+        else {
+            return Ok(None);
+        };
+        let side = if position.position_amt.is_sign_positive() {
+            OrderSide::Sell
+        } else {
+            OrderSide::Buy
+        };
+        let params = types::request::NewOrder {
+            symbol: symbol.into(),
+            side,
+            order_type: OrderType::Market,
+            position_side: Some(position_side),
+            quantity: Some(position.position_amt.abs()),
+            reduce_only: (position_side == PositionSide::Both).then_some(true),
+            new_client_order_id: Some(self.generate_client_order_id()),
+            ..Default::default()
+        };
+        Ok(Some(self.client.new_order(params).await?))
+    }
+
     pub async fn cancel_order(&self, params: OrderId, is_algo: bool) -> Result<()> {
         if is_algo {
-            self.0.cancel_algo_order(params.into()).await?;
+            self.client.cancel_algo_order(params.into()).await?;
         } else {
-            self.0.cancel_order(params).await?;
+            self.client.cancel_order(params).await?;
         }
         Ok(())
     }
 
+    /// Cancel an order and confirm it's canceled by querying it, the status of a canceled order
+    /// may take a short time to be updated.
+    pub async fn cancel_and_confirm(&self, symbol: &str, order_id: i64) -> Result<()> {
+        self.client
+            .cancel_order(OrderId::new_bn(symbol, order_id))
+            .await?;
+        for _ in 0..CANCEL_CONFIRM_TIMES {
+            let order = self
+                .client
+                .query_order(OrderId::new_bn(symbol, order_id))
+                .await?;
+            match order.status {
+                OrderStatus::Canceled => return Ok(()),
+                status if !status.is_open() => {
+                    return Err(Error::new(&format!(
+                        "order {order_id} is {status} instead of canceled"
+                    )));
+                }
+                _ => tokio::time::sleep(CANCEL_CONFIRM_INTERVAL).await,
+            }
+        }
+        Err(Error::new(&format!(
+            "order {order_id} is still open after canceled"
+        )))
+    }
+
+    /// Cancel an order and place a new one, both the canceled and the new order are returned,
+    /// so the quantity filled before the cancel is known. If the cancel fails because the order
+    /// is filled already, the filled order is returned without placing the new one.
+    pub async fn cancel_replace(
+        &self,
+        symbol: &str,
+        order_id: i64,
+        new: types::request::NewOrder,
+    ) -> Result<CancelReplace> {
+        // don't cancel the order if the new one would be rejected
+        new.validate()?;
+        let old = match self
+            .client
+            .cancel_order(OrderId::new_bn(symbol, order_id))
+            .await
+        {
+            Ok(old) => old,
+            Err(e) => {
+                // the cancel error is kept if the order can't be queried either
+                return match self
+                    .client
+                    .query_order(OrderId::new_bn(symbol, order_id))
+                    .await
+                {
+                    Ok(order) if order.status == OrderStatus::Filled => Ok(CancelReplace {
+                        old: order,
+                        new: None,
+                    }),
+                    _ => Err(e),
+                };
+            }
+        };
+        let new = self.client.new_order(new).await?;
+        Ok(CancelReplace {
+            old,
+            new: Some(new),
+        })
+    }
+
+    /// Poll an order until it's filled, canceled, rejected or expired, an error is returned if
+    /// it's still open after the timeout.
+    pub async fn wait_for_fill(
+        &self,
+        symbol: &str,
+        order_id: i64,
+        timeout: Duration,
+    ) -> Result<types::response::OrderInfo> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let order = self
+                .client
+                .query_order(OrderId::new_bn(symbol, order_id))
+                .await?;
+            if order.status.is_final() {
+                return Ok(order);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::new(&format!(
+                    "order {order_id} is still {} after {timeout:?}",
+                    order.status
+                )));
+            }
+            tokio::time::sleep(self.poll_interval.min(deadline - now)).await;
+        }
+    }
+
     pub async fn query_order(&self, params: OrderId, is_algo: bool) -> Result<NewOrderResult> {
         Ok(if is_algo {
-            self.0.query_algo_order(params.into()).await?.into()
+            self.client.query_algo_order(params.into()).await?.into()
         } else {
-            self.0.query_order(params).await?.into()
+            self.client.query_order(params).await?.into()
         })
     }
+
+    /// Get the fills of an order
+    pub async fn order_fills(
+        &self,
+        symbol: &str,
+        order_id: u64,
+    ) -> Result<Vec<types::response::AccountTradeList>> {
+        let params = types::request::AccountTradeList {
+            symbol: symbol.into(),
+            order_id: Some(order_id),
+            start_time: None,
+            end_time: None,
+            from_id: None,
+            limit: None,
+        };
+        self.client.account_trade_list(params).await
+    }
+
+    /// Get all account orders created in `[start, end]`, the time range is split into pages.
+    pub fn all_orders_paged(
+        &self,
+        symbol: &str,
+        start: i64,
+        end: i64,
+    ) -> impl Stream<Item = Result<types::response::OrderInfo>> + use<'a> {
+        let client = self.client;
+        let symbol = types::Symbol::from(symbol);
+        paginate(
+            start,
+            end,
+            move |start_time, end_time| {
+                client.all_orders(types::request::AllOrders {
+                    symbol: symbol.clone(),
+                    order_id: None,
+                    start_time: Some(start_time),
+                    end_time: Some(end_time),
+                    limit: Some(HISTORY_LIMIT as u64),
+                })
+            },
+            |i: &types::response::OrderInfo| (i.time.unwrap_or(i.update_time), i.order_id as i64),
+        )
+    }
+
+    /// Get all account trades in `[start, end]`, the time range is split into pages.
+    pub fn account_trade_list_paged(
+        &self,
+        symbol: &str,
+        start: i64,
+        end: i64,
+    ) -> impl Stream<Item = Result<types::response::AccountTradeList>> + use<'a> {
+        let client = self.client;
+        let symbol = types::Symbol::from(symbol);
+        paginate(
+            start,
+            end,
+            move |start_time, end_time| {
+                client.account_trade_list(types::request::AccountTradeList {
+                    symbol: symbol.clone(),
+                    order_id: None,
+                    start_time: Some(start_time),
+                    end_time: Some(end_time),
+                    from_id: None,
+                    limit: Some(HISTORY_LIMIT as i64),
+                })
+            },
+            |i: &types::response::AccountTradeList| (i.time, i.id),
+        )
+    }
+
+    /// Income history in `[start, end]` of all symbols, the time range is walked window by
+    /// window like `account_trade_list_paged`.
+    pub fn income_history_paged(
+        &self,
+        income_type: Option<IncomeType>,
+        start: i64,
+        end: i64,
+    ) -> impl Stream<Item = Result<types::response::IncomeHistory>> + use<'a> {
+        let client = self.client;
+        paginate(
+            start,
+            end,
+            move |start_time, end_time| {
+                client.income_history(types::request::IncomeHistory {
+                    income_type,
+                    start_time: Some(start_time),
+                    end_time: Some(end_time),
+                    limit: Some(HISTORY_LIMIT as u32),
+                    ..Default::default()
+                })
+            },
+            |i: &types::response::IncomeHistory| (i.time, i.tran_id),
+        )
+    }
+
+    /// Realized pnl, funding fees and commissions in `[start, end]` summed by UTC day and asset,
+    /// e.g. commissions paid in BNB are summed apart from the pnl in USDT.
+    #[cfg(feature = "chrono")]
+    pub async fn daily_pnl(
+        &self,
+        start: i64,
+        end: i64,
+    ) -> Result<BTreeMap<(chrono::NaiveDate, String), Decimal>> {
+        self.income_history_paged(None, start, end)
+            .try_fold(BTreeMap::new(), |mut pnl, i| async move {
+                if matches!(
+                    i.income_type,
+                    IncomeType::RealizedPnl | IncomeType::FundingFee | IncomeType::Commission
+                ) {
+                    let day = timestamp::chrono::utc_date(i.time)
+                        .ok_or_else(|| Error::new(&format!("invalid income time: {}", i.time)))?;
+                    *pnl.entry((day, i.asset)).or_default() += i.income;
+                }
+                Ok::<_, Error>(pnl)
+            })
+            .await
+    }
+
+    /// Realized pnl of trades in `[start, end]` net of commission, in the margin asset of the
+    /// symbol. Commissions paid in other assets, e.g. BNB, are not deducted.
+    pub async fn realized_pnl_by_symbol(
+        &self,
+        symbol: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Decimal> {
+        // symbols of the exchange info are uppercase
+        let symbol = types::Symbol::from(symbol);
+        let margin_asset = self
+            .symbol_info(symbol.as_str())
+            .await?
+            .ok_or_else(|| Error::new(&format!("unknown symbol: {symbol}")))?
+            .margin_asset;
+        self.account_trade_list_paged(symbol.as_str(), start, end)
+            .try_fold(Decimal::ZERO, |pnl, i| {
+                let commission = if i.commission_asset == margin_asset {
+                    i.commission
+                } else {
+                    Decimal::ZERO
+                };
+                async move { Ok(pnl + i.realized_pnl - commission) }
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    /// Rules of a symbol whose market lot size differs from the lot size
+    fn symbol_info() -> types::response::ExchangeInfoSymbol {
+        serde_json::from_value(serde_json::json!({
+            "symbol": "BTCUSDT",
+            "pair": "BTCUSDT",
+            "contractType": "PERPETUAL",
+            "deliveryDate": 4133404800000i64,
+            "onboardDate": 1569398400000i64,
+            "status": "TRADING",
+            "maintMarginPercent": "2.5000",
+            "requiredMarginPercent": "5.0000",
+            "baseAsset": "BTC",
+            "quoteAsset": "USDT",
+            "marginAsset": "USDT",
+            "pricePrecision": 2,
+            "quantityPrecision": 3,
+            "baseAssetPrecision": 8,
+            "quotePrecision": 8,
+            "underlyingType": "COIN",
+            "underlyingSubType": ["PoW"],
+            "triggerProtect": "0.0500",
+            "filters": [
+                {
+                    "filterType": "PRICE_FILTER",
+                    "maxPrice": "4529764",
+                    "minPrice": "556.80",
+                    "tickSize": "0.10"
+                },
+                {
+                    "filterType": "LOT_SIZE",
+                    "maxQty": "1000",
+                    "minQty": "0.001",
+                    "stepSize": "0.001"
+                },
+                {
+                    "filterType": "MARKET_LOT_SIZE",
+                    "maxQty": "120",
+                    "minQty": "0.01",
+                    "stepSize": "0.01"
+                }
+            ],
+            "orderTypes": ["LIMIT", "MARKET"],
+            "timeInForce": ["GTC", "IOC"],
+            "liquidationFee": "0.012500",
+            "marketTakeBound": "0.05"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn kline_windows_of_range() {
+        let minute = 60_000;
+        let window = minute * KLINE_LIMIT;
+        let start = 30_000;
+        let end = start + 3200 * minute;
+        assert_eq!(
+            kline_windows(types::KlineInterval::I1m, start, end),
+            vec![
+                (0, window - 1),
+                (window, 2 * window - 1),
+                (2 * window, end - 1)
+            ]
+        );
+        assert_eq!(
+            kline_windows(types::KlineInterval::I1m, 0, window),
+            vec![(0, window - 1)]
+        );
+        assert!(kline_windows(types::KlineInterval::I1m, minute, minute).is_empty());
+    }
+
+    #[test]
+    fn round_by_lot_size_of_order_type() {
+        let rules = symbol_info();
+        let mut order = NewOrder::LimitMatch {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            quantity: dec("0.1237"),
+            price_match: PriceMatch::Queue,
+            reduce_only: None,
+            position_side: None,
+            client_order_id: None,
+        };
+        order.round(&rules).unwrap();
+        assert!(matches!(order, NewOrder::LimitMatch { quantity, .. } if quantity == dec("0.123")));
+
+        let mut order = NewOrder::Market {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            quantity: dec("0.1237"),
+            reduce_only: None,
+            position_side: None,
+            client_order_id: None,
+        };
+        order.round(&rules).unwrap();
+        assert!(matches!(order, NewOrder::Market { quantity, .. } if quantity == dec("0.12")));
+    }
 }
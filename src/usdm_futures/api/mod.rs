@@ -1,45 +1,53 @@
-use std::{
-    fmt,
-    time::{Duration, SystemTime},
-};
+use std::{fmt, time::Duration};
 
-use hmac::{Hmac, Mac};
-use reqwest::{Method, Proxy};
 use serde::{Serialize, de::DeserializeOwned};
-use sha2::Sha256;
-use tracing::debug;
 
 use crate::{
     error::{Error, Result},
-    usdm_futures::types::{request, response},
+    rest::RestClient,
+    usdm_futures::types::{NewOrderRespType, request, response},
 };
 
 pub mod extend;
+pub mod weight;
 
-pub struct Client {
-    auth: Option<Auth>,
-    client: reqwest::Client,
-}
+pub use reqwest::Method;
+
+pub use crate::rest::{MetricsCallback, RetryPolicy};
+
+/// Max number of orders of `place_multiple_orders`
+pub const MAX_BATCH_ORDERS: usize = 5;
+
+const BASE_URL: &str = "https://fapi.binance.com";
+const TIME_ENDPOINT: &str = "fapi/v1/time";
 
-struct Auth {
-    key: String,
-    secret: Option<String>,
+#[derive(Debug, Clone)]
+pub struct Client {
+    rest: RestClient,
 }
 
-enum ApiVersion {
+/// Path prefix of endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    /// `fapi/v1`
     V1,
+    /// `fapi/v2`
     V2,
+    /// `fapi/v3`
     V3,
+    /// `futures/data`
     Data,
 }
 
-struct Endpoint {
+/// Endpoint path without the version prefix, `&str` is in `ApiVersion::V1`
+#[derive(Debug, Clone)]
+pub struct Endpoint {
     version: ApiVersion,
     endpoint: String,
 }
 
 impl Endpoint {
-    fn new(version: ApiVersion, endpoint: &str) -> Self {
+    pub fn new(version: ApiVersion, endpoint: &str) -> Self {
         Endpoint {
             version,
             endpoint: endpoint.to_string(),
@@ -73,124 +81,104 @@ impl fmt::Display for Endpoint {
 
 impl Client {
     pub fn new(key: Option<&str>, secret: Option<&str>, proxy: Option<&str>) -> Result<Self> {
-        let mut client_builder = reqwest::Client::builder();
-        if let Some(proxy) = proxy {
-            let proxy = Proxy::all(proxy)?;
-            client_builder = client_builder.proxy(proxy);
-        }
-        let client = client_builder.build()?;
+        let rest = RestClient::new(BASE_URL, TIME_ENDPOINT, key, secret, proxy)?;
+        Ok(Client { rest })
+    }
 
-        let auth = key.map(|key| Auth {
-            key: key.to_string(),
-            secret: secret.map(|i| i.to_string()),
-        });
-        Ok(Client { auth, client })
+    /// Same as `new` with a pre-built `reqwest::Client`, e.g. with custom TLS roots, HTTP/2
+    /// tuning or a connection pool shared by clients. The proxy is configured on the client.
+    pub fn from_reqwest(client: reqwest::Client, key: Option<&str>, secret: Option<&str>) -> Self {
+        let rest = RestClient::from_reqwest(BASE_URL, TIME_ENDPOINT, client, key, secret);
+        Client { rest }
     }
 
-    fn url<E: Into<Endpoint>>(endpoint: E) -> String {
-        let endpoint: Endpoint = endpoint.into();
-        format!("https://fapi.binance.com/{endpoint}")
+    /// Enable retry of failed requests
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.rest = self.rest.with_retry(policy);
+        self
     }
 
-    fn auth(&self) -> Result<&Auth> {
-        self.auth
-            .as_ref()
-            .ok_or_else(|| Error::new("auth in client is required"))
+    /// Report the cost of successful calls, e.g. to metrics, see `MetricsCallback`
+    pub fn with_metrics(
+        mut self,
+        callback: impl Fn(&str, Duration, Duration, Duration) + Send + Sync + 'static,
+    ) -> Self {
+        self.rest = self.rest.with_metrics(callback);
+        self
     }
 
-    async fn call_with_request<RESP: DeserializeOwned>(
-        &self,
-        request: reqwest::Request,
-    ) -> Result<RESP> {
-        let start_time = SystemTime::now();
-        let res = self.client.execute(request).await?;
-        let call_cost = fmt_duration(start_time.elapsed()?);
-        if res.status().is_success() {
-            let start_time = SystemTime::now();
-            let s = res.text().await?;
-            let read_cost = fmt_duration(start_time.elapsed()?);
-            let start_time = SystemTime::now();
-            let r = serde_json::from_str(&s)?;
-            let serde_cost = fmt_duration(start_time.elapsed()?);
-            debug!(
-                "call binance api call cost {call_cost}, read cost: {read_cost}, serde cost: {serde_cost}"
-            );
-            Ok(r)
-        } else {
-            Err(Error::new(&format!(
-                "binance api error, http code: {}, body: {}",
-                res.status(),
-                res.text().await?
-            )))
-        }
+    /// Sync the offset between local time and binance server time, the offset is applied to
+    /// the timestamp of signed requests.
+    pub async fn sync_time(&self) -> Result<()> {
+        self.rest.sync_time().await
     }
 
-    async fn call<E, REQ, RESP>(&self, url: E, method: Method, req: REQ) -> Result<RESP>
+    /// Call a public endpoint which is not wrapped yet, e.g.
+    /// `client.call((ApiVersion::V1, "premiumIndex"), Method::GET, params)`
+    pub async fn call<E, REQ, RESP>(&self, url: E, method: Method, req: REQ) -> Result<RESP>
     where
         E: Into<Endpoint>,
         REQ: Serialize,
         RESP: DeserializeOwned,
     {
-        let request = self
-            .client
-            .request(method, Self::url(url))
-            .query(&req)
-            .build()?;
-        self.call_with_request(request).await
+        let endpoint: Endpoint = url.into();
+        self.rest.call(&endpoint.to_string(), method, req).await
     }
 
-    async fn call_with_key<E, REQ, RESP>(&self, url: E, method: Method, req: REQ) -> Result<RESP>
+    /// Call an endpoint which requires the api key but no signature
+    pub async fn call_with_key<E, REQ, RESP>(
+        &self,
+        url: E,
+        method: Method,
+        req: REQ,
+    ) -> Result<RESP>
     where
         E: Into<Endpoint>,
         REQ: Serialize,
         RESP: DeserializeOwned,
     {
-        let auth = self.auth()?;
-        let request = self
-            .client
-            .request(method, Self::url(url))
-            .header("X-MBX-APIKEY", &auth.key)
-            .query(&req)
-            .build()?;
-        self.call_with_request(request).await
+        let endpoint: Endpoint = url.into();
+        self.rest
+            .call_with_key(&endpoint.to_string(), method, req)
+            .await
     }
 
-    async fn signed_call<E, REQ, RESP>(&self, url: E, method: Method, req: REQ) -> Result<RESP>
+    /// Call a signed endpoint, the timestamp and signature are appended to the parameters
+    pub async fn signed_call<E, REQ, RESP>(&self, url: E, method: Method, req: REQ) -> Result<RESP>
     where
         E: Into<Endpoint>,
         REQ: Serialize,
         RESP: DeserializeOwned,
     {
-        let auth = self.auth()?;
-        let timestamp = chrono::Utc::now().timestamp_millis();
-
-        let mut request = self
-            .client
-            .request(method, Self::url(url))
-            .header("X-MBX-APIKEY", &auth.key)
-            .query(&req)
-            .query(&[("timestamp", timestamp.to_string())])
-            .build()?;
-
-        let secret = auth
-            .secret
-            .as_ref()
-            .ok_or_else(|| Error::new("secret is required"))?;
-        let mut mac: Hmac<Sha256> = Hmac::new_from_slice(secret.as_bytes())?;
-        mac.update(request.url().query().unwrap_or_default().as_bytes());
-        let mac_result = mac.finalize();
-        let signature = hex::encode(mac_result.into_bytes());
-        request
-            .url_mut()
-            .query_pairs_mut()
-            .append_pair("signature", &signature);
-
-        self.call_with_request(request).await
+        let endpoint: Endpoint = url.into();
+        self.rest
+            .signed_call(&endpoint.to_string(), method, req)
+            .await
+    }
+
+    /// Call a signed endpoint which is not modeled yet and get the raw json, e.g.
+    /// `client.signed_call_value((ApiVersion::V1, "adlQuantile"), Method::GET, params)`
+    pub async fn signed_call_value<E, REQ>(
+        &self,
+        url: E,
+        method: Method,
+        req: REQ,
+    ) -> Result<serde_json::Value>
+    where
+        E: Into<Endpoint>,
+        REQ: Serialize,
+    {
+        self.signed_call(url, method, req).await
     }
 }
 
 // market data
 impl Client {
+    /// Check server time
+    pub async fn check_server_time(&self) -> Result<response::ServerTime> {
+        self.call("time", Method::GET, None::<()>).await
+    }
+
     pub async fn exchange_info(&self) -> Result<response::ExchangeInfo> {
         self.call("exchangeInfo", Method::GET, None::<()>).await
     }
@@ -217,6 +205,15 @@ impl Client {
         self.call("klines", Method::GET, params).await
     }
 
+    /// Same as `kline_candlestick_data`, but parse the raw arrays into typed klines
+    pub async fn klines_typed(
+        &self,
+        params: request::KlineCandlestickData,
+    ) -> Result<Vec<response::KlineCandlestickData>> {
+        let v = self.kline_candlestick_data(params).await?;
+        v.into_iter().map(TryInto::try_into).collect()
+    }
+
     pub async fn open_interest_hist(
         &self,
         params: request::OpenInterestHist,
@@ -225,6 +222,30 @@ impl Client {
             .await
     }
 
+    /// Basis of futures to the index
+    pub async fn futures_basis(&self, params: request::Basis) -> Result<Vec<response::Basis>> {
+        self.call((ApiVersion::Data, "basis"), Method::GET, params)
+            .await
+    }
+
+    /// Settlement prices of the quarterly contracts of a pair
+    pub async fn delivery_price(
+        &self,
+        params: request::DeliveryPrice,
+    ) -> Result<Vec<response::DeliveryPrice>> {
+        self.call((ApiVersion::Data, "delivery-price"), Method::GET, params)
+            .await
+    }
+
+    pub async fn order_book(&self, params: request::OrderBook) -> Result<response::OrderBook> {
+        self.call("depth", Method::GET, params).await
+    }
+
+    /// Composite index symbol information, e.g. `DEFIUSDT`
+    pub async fn index_info(&self, params: request::OptionalSymbol) -> Result<response::IndexInfo> {
+        self.call("indexInfo", Method::GET, params).await
+    }
+
     pub async fn historical_trades(
         &self,
         params: request::HistoricalTrades,
@@ -238,14 +259,34 @@ impl Client {
 impl Client {
     /// Send in a new order
     pub async fn new_order(&self, params: request::NewOrder) -> Result<response::OrderInfo> {
+        params.validate()?;
+        self.signed_call("order", Method::POST, params).await
+    }
+
+    /// Send in a new order with `newOrderRespType=ACK`, the response is returned as soon as
+    /// the order is accepted, without waiting for the result of matching.
+    pub async fn new_order_ack(&self, mut params: request::NewOrder) -> Result<response::OrderAck> {
+        params.validate()?;
+        params.new_order_resp_type = Some(NewOrderRespType::Ack);
         self.signed_call("order", Method::POST, params).await
     }
 
     /// Place Multiple Orders
+    /// Results are in the same order as the orders, a failed order is an `Error::Binance` without
+    /// failing the others.
     pub async fn place_multiple_orders(
         &self,
         params: Vec<request::NewOrder>,
-    ) -> Result<Vec<response::OrderInfo>> {
+    ) -> Result<Vec<Result<response::OrderInfo>>> {
+        if params.len() > MAX_BATCH_ORDERS {
+            return Err(Error::new(&format!(
+                "too many orders: {}, at most {MAX_BATCH_ORDERS} orders per batch",
+                params.len()
+            )));
+        }
+        for i in &params {
+            i.validate()?;
+        }
         #[derive(Serialize)]
         #[serde(rename_all = "camelCase")]
         struct Params {
@@ -254,11 +295,21 @@ impl Client {
         let params = Params {
             batch_orders: serde_json::to_value(params)?,
         };
-        self.signed_call("batchOrders", Method::POST, params).await
+        let orders: Vec<response::BatchOrder> = self
+            .signed_call("batchOrders", Method::POST, params)
+            .await?;
+        Ok(orders
+            .into_iter()
+            .map(|i| match i {
+                response::BatchOrder::Error { code, msg } => Err(Error::BatchOrder { code, msg }),
+                response::BatchOrder::Order(order) => Ok(*order),
+            })
+            .collect())
     }
 
     /// Modify Order
     pub async fn modify_order(&self, params: request::ModifyOrder) -> Result<response::OrderInfo> {
+        params.validate()?;
         self.signed_call("order", Method::PUT, params).await
     }
 
@@ -368,8 +419,10 @@ impl Client {
     // TODO: Position ADL Quantile Estimation
     // TODO: Get Position Margin Change History
 
-    /// Testing order request, this order will not be submitted to matching engine
-    pub async fn test_order(&self, params: request::NewOrder) -> Result<response::OrderInfo> {
+    /// Testing order request, this order will not be submitted to matching engine. The
+    /// response is an empty object if the order is valid.
+    pub async fn test_order(&self, params: request::NewOrder) -> Result<response::OperationResult> {
+        params.validate()?;
         self.signed_call("order/test", Method::POST, params).await
     }
 
@@ -398,6 +451,16 @@ impl Client {
     ) -> Result<response::AlgoOrderInfo> {
         self.signed_call("algoOrder", Method::GET, params).await
     }
+
+    /// # Current All Algo Open Orders
+    /// Get all algo open orders on a symbol, or all symbols if symbol is not sent.
+    pub async fn current_algo_open_orders(
+        &self,
+        params: request::OptionalSymbol,
+    ) -> Result<Vec<response::AlgoOrderInfo>> {
+        self.signed_call("openAlgoOrders", Method::GET, params)
+            .await
+    }
 }
 
 // account
@@ -442,17 +505,75 @@ impl Client {
         )
         .await
     }
+
+    /// Get Current Multi-Assets Mode
+    pub async fn get_multi_assets_mode(&self) -> Result<response::GetMultiAssetsMode> {
+        self.signed_call(
+            (ApiVersion::V1, "multiAssetsMargin"),
+            Method::GET,
+            None::<()>,
+        )
+        .await
+    }
+
+    /// # Futures Trading Quantitative Rules Indicators
+    /// Get the trading rules indicators of all symbols, and whether the account is locked by them.
+    pub async fn api_trading_status(&self) -> Result<response::ApiTradingStatus> {
+        self.signed_call(
+            (ApiVersion::V1, "apiTradingStatus"),
+            Method::GET,
+            None::<()>,
+        )
+        .await
+    }
+
+    /// # Get Income History
+    pub async fn income_history(
+        &self,
+        params: request::IncomeHistory,
+    ) -> Result<Vec<response::IncomeHistory>> {
+        self.signed_call((ApiVersion::V1, "income"), Method::GET, params)
+            .await
+    }
+
+    /// # Get Download Id For Futures Transaction History
+    /// Start an asynchronous export of the transaction history, the link is queried by the
+    /// returned download id.
+    pub async fn get_download_id_transaction_history(
+        &self,
+        params: request::DownloadIdTransactionHistory,
+    ) -> Result<response::DownloadIdTransactionHistory> {
+        self.signed_call((ApiVersion::V1, "income/asyn"), Method::GET, params)
+            .await
+    }
+
+    /// # Get Futures Transaction History Download Link by Id
+    pub async fn get_download_link(
+        &self,
+        params: request::DownloadId,
+    ) -> Result<response::DownloadLink> {
+        self.signed_call((ApiVersion::V1, "income/asyn/id"), Method::GET, params)
+            .await
+    }
 }
 
-fn fmt_duration(d: Duration) -> String {
-    if d.as_millis() == 0 {
-        format!("{}us", d.as_micros())
-    } else {
-        let millis = d.as_millis();
-        if millis >= 1000 {
-            format!("{}s", millis as f64 / 1000.0)
-        } else {
-            format!("{}ms", millis)
-        }
+// user data streams
+impl Client {
+    /// Start a user data stream, the listen key is valid for 60 minutes unless it's kept alive
+    pub async fn start_user_data_stream(&self) -> Result<response::ListenKey> {
+        self.call_with_key("listenKey", Method::POST, None::<()>)
+            .await
+    }
+
+    /// Extend the validity of the listen key by 60 minutes
+    pub async fn keepalive_user_data_stream(&self) -> Result<response::ListenKey> {
+        self.call_with_key("listenKey", Method::PUT, None::<()>)
+            .await
+    }
+
+    pub async fn close_user_data_stream(&self) -> Result<()> {
+        self.call_with_key::<_, _, serde_json::Value>("listenKey", Method::DELETE, None::<()>)
+            .await?;
+        Ok(())
     }
 }
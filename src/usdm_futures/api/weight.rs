@@ -0,0 +1,86 @@
+//! Documented request weights of the methods of `Client` on the IP rate limit, which is reported
+//! by the `X-MBX-USED-WEIGHT-1M` header.
+//!
+//! Order placements weigh 0 on the IP rate limit, they are counted by the order rate limits.
+
+use crate::usdm_futures::types::request;
+
+pub const CHECK_SERVER_TIME: u32 = 1;
+pub const EXCHANGE_INFO: u32 = 1;
+pub const HISTORICAL_TRADES: u32 = 20;
+
+pub const NEW_ORDER: u32 = 0;
+pub const NEW_ORDER_ACK: u32 = 0;
+pub const PLACE_MULTIPLE_ORDERS: u32 = 5;
+pub const MODIFY_ORDER: u32 = 0;
+pub const CANCEL_ORDER: u32 = 1;
+pub const QUERY_ORDER: u32 = 1;
+pub const ALL_ORDERS: u32 = 5;
+pub const QUERY_CURRENT_OPEN_ORDER: u32 = 1;
+pub const ACCOUNT_TRADE_LIST: u32 = 5;
+pub const CHANGE_MARGIN_TYPE: u32 = 1;
+pub const CHANGE_POSITION_MODE: u32 = 1;
+pub const CHANGE_INITIAL_LEVERAGE: u32 = 1;
+pub const CHANGE_MULTI_ASSETS_MODE: u32 = 1;
+pub const MODIFY_ISOLATED_POSITION_MARGIN: u32 = 1;
+pub const POSITION_INFORMATION_V3: u32 = 5;
+pub const TEST_ORDER: u32 = 0;
+pub const NEW_ALGO_ORDER: u32 = 0;
+pub const CANCEL_ALGO_ORDER: u32 = 1;
+pub const QUERY_ALGO_ORDER: u32 = 1;
+
+pub const FUTURES_ACCOUNT_BALANCE_V3: u32 = 5;
+pub const ACCOUNT_INFORMATION_V3: u32 = 5;
+pub const FUTURES_ACCOUNT_CONFIGURATION: u32 = 5;
+pub const SYMBOL_CONFIGURATION: u32 = 5;
+pub const GET_CURRENT_POSITION_MODE: u32 = 30;
+pub const GET_MULTI_ASSETS_MODE: u32 = 30;
+pub const API_TRADING_STATUS: u32 = 10;
+pub const INCOME_HISTORY: u32 = 30;
+pub const GET_DOWNLOAD_ID_TRANSACTION_HISTORY: u32 = 1000;
+pub const GET_DOWNLOAD_LINK: u32 = 10;
+
+/// Weight of a request for one symbol or all symbols
+const fn by_symbol(params: &request::OptionalSymbol, all: u32) -> u32 {
+    if params.symbol.is_some() { 1 } else { all }
+}
+
+pub const fn ticker_24hr(params: &request::OptionalSymbol) -> u32 {
+    by_symbol(params, 40)
+}
+
+pub const fn ticker_price(params: &request::OptionalSymbol) -> u32 {
+    by_symbol(params, 2)
+}
+
+pub const fn index_info(params: &request::OptionalSymbol) -> u32 {
+    by_symbol(params, 10)
+}
+
+/// Weight of `kline_candlestick_data` and `klines_typed` by the limit, default limit is 500
+pub const fn klines(params: &request::KlineCandlestickData) -> u32 {
+    match params.limit {
+        Some(limit) if limit < 100 => 1,
+        Some(limit) if limit < 500 => 2,
+        Some(limit) if limit > 1000 => 10,
+        _ => 5,
+    }
+}
+
+/// Weight of `order_book` by the limit, default limit is 500
+pub const fn order_book(params: &request::OrderBook) -> u32 {
+    match params.limit {
+        Some(limit) if limit <= 50 => 2,
+        Some(limit) if limit <= 100 => 5,
+        Some(limit) if limit > 500 => 20,
+        _ => 10,
+    }
+}
+
+pub const fn current_all_open_orders(params: &request::OptionalSymbol) -> u32 {
+    by_symbol(params, 40)
+}
+
+pub const fn current_algo_open_orders(params: &request::OptionalSymbol) -> u32 {
+    by_symbol(params, 40)
+}
@@ -1,3 +1,6 @@
 pub mod api;
+pub mod order_book;
+pub mod position_tracker;
 pub mod stream;
 pub mod types;
+pub mod ws_api;
@@ -0,0 +1,471 @@
+//! Local order book kept by a depth snapshot and the diff depth stream
+
+use std::{collections::BTreeMap, ops::ControlFlow};
+
+use futures_util::StreamExt;
+use rust_decimal::Decimal;
+
+use crate::{
+    error::{Error, Result},
+    log::warn,
+    usdm_futures::{
+        api::Client,
+        stream::{self, RECONNECT_DELAY, request::Stream, response::StreamItem},
+        types::{OrderSide, Symbol, request, response},
+    },
+};
+
+/// Max depth of the snapshot
+const SNAPSHOT_LIMIT: u16 = 1000;
+/// Times to fetch the snapshot again if it fails, e.g. it's older than the buffered updates
+const SNAPSHOT_RETRIES: u32 = 3;
+
+#[derive(Debug)]
+struct Update {
+    first_update_id: u64,
+    final_update_id: u64,
+    prev_final_update_id: u64,
+    bids: Vec<(Decimal, Decimal)>,
+    asks: Vec<(Decimal, Decimal)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Updates are buffered until the snapshot is set
+    Buffering,
+    /// The snapshot is set, waiting for the first update after its `lastUpdateId`
+    Snapshot(u64),
+    /// `u` of the last applied update
+    Synced(u64),
+}
+
+/// Order book of a symbol kept in sync with the `<symbol>@depth` stream.
+///
+/// Updates received before the snapshot are buffered, then applied by the documented steps:
+/// updates with `u` < `lastUpdateId` of the snapshot are dropped, the first update should
+/// have `U` <= `lastUpdateId` <= `u`, and `pu` of every following update should be `u` of the
+/// previous one. If an update is missing, the book is reset and an error is returned, `sync`
+/// should be called again to rebuild it.
+#[derive(Debug)]
+pub struct LocalOrderBook {
+    symbol: Symbol,
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+    state: State,
+    buffer: Vec<Update>,
+}
+
+impl LocalOrderBook {
+    pub fn new(symbol: &str) -> Self {
+        LocalOrderBook {
+            symbol: symbol.into(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            state: State::Buffering,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn symbol(&self) -> &Symbol {
+        &self.symbol
+    }
+
+    /// The snapshot is set and the updates after it are applied
+    pub fn is_synced(&self) -> bool {
+        matches!(self.state, State::Synced(_))
+    }
+
+    /// Fetch the snapshot and apply the buffered updates, the depth stream should be
+    /// subscribed before, so no update between them is missed.
+    pub async fn sync(&mut self, client: &Client) -> Result<()> {
+        let snapshot = client
+            .order_book(request::OrderBook {
+                symbol: self.symbol.clone(),
+                limit: Some(SNAPSHOT_LIMIT),
+            })
+            .await?;
+        self.set_snapshot(snapshot)
+    }
+
+    /// Replace the book by the snapshot and apply the buffered updates. If the snapshot is
+    /// older than the buffered updates, an error is returned and the updates are kept
+    /// buffered, so a newer snapshot can be set.
+    pub fn set_snapshot(&mut self, snapshot: response::OrderBook) -> Result<()> {
+        let last_update_id = snapshot.last_update_id;
+        if let Some(update) = self
+            .buffer
+            .iter()
+            .find(|i| i.final_update_id >= last_update_id)
+            && update.first_update_id > last_update_id
+        {
+            return Err(Error::new(&format!(
+                "snapshot {last_update_id} of {} is older than the buffered update {}",
+                self.symbol, update.first_update_id
+            )));
+        }
+        self.bids = snapshot.bids.into_iter().collect();
+        self.asks = snapshot.asks.into_iter().collect();
+        self.state = State::Snapshot(snapshot.last_update_id);
+        for update in std::mem::take(&mut self.buffer) {
+            self.apply_update(update)?;
+        }
+        Ok(())
+    }
+
+    /// Apply an item of the depth stream, items of other events or symbols are ignored
+    pub fn apply(&mut self, item: StreamItem) -> Result<()> {
+        let StreamItem::DepthUpdate {
+            symbol,
+            first_update_id,
+            final_update_id,
+            prev_final_update_id,
+            bids,
+            asks,
+            ..
+        } = item
+        else {
+            return Ok(());
+        };
+        if self.symbol.as_str() != symbol {
+            return Ok(());
+        }
+        self.apply_update(Update {
+            first_update_id,
+            final_update_id,
+            prev_final_update_id,
+            bids,
+            asks,
+        })
+    }
+
+    /// Clear the book and buffer updates until the next snapshot
+    pub fn reset(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.state = State::Buffering;
+        self.buffer.clear();
+    }
+
+    /// Updates are missed while the depth stream is disconnected, the book is reset and
+    /// `sync` should be called again after the stream is subscribed on the new connection
+    pub fn on_reconnect(&mut self) {
+        self.reset();
+    }
+
+    /// Keep the book synced by the depth stream until `on_update` breaks, `on_update` is called
+    /// after every applied update of a synced book.
+    ///
+    /// The snapshot is fetched after the first update is received, so the stream is
+    /// subscribed before. It's fetched again on the next update if it fails, at most
+    /// `SNAPSHOT_RETRIES` times in a row while the stream keeps running. The book is synced
+    /// again on missing updates, and rebuilt on a new connection if the connection is closed
+    /// or failed.
+    pub async fn run(
+        &mut self,
+        client: &Client,
+        update_speed: Option<u16>,
+        proxy: Option<&str>,
+        mut on_update: impl FnMut(&LocalOrderBook) -> ControlFlow<()>,
+    ) {
+        let symbol = self.symbol.clone();
+        let depth = Stream::DiffBookDepth {
+            symbol: symbol.clone(),
+            update_speed,
+        };
+        loop {
+            self.on_reconnect();
+            let (tx, mut rx) = futures_channel::mpsc::unbounded();
+            let receive = stream::receive(vec![depth.clone()], tx, proxy);
+            let consume = async {
+                let mut snapshot_failures = 0;
+                while let Some(s) = rx.next().await {
+                    for item in s.streams {
+                        if let Err(e) = self.apply(item) {
+                            warn!("{e}, sync again");
+                        }
+                    }
+                    if self.state == State::Buffering {
+                        match self.sync(client).await {
+                            Ok(()) => snapshot_failures = 0,
+                            Err(e) if snapshot_failures < SNAPSHOT_RETRIES => {
+                                snapshot_failures += 1;
+                                warn!("{e}, fetch the snapshot again");
+                            }
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    if self.is_synced() && on_update(self).is_break() {
+                        return Ok(ControlFlow::Break(()));
+                    }
+                }
+                Ok::<_, Error>(ControlFlow::Continue(()))
+            };
+            tokio::select! {
+                result = receive => match result {
+                    Ok(()) => warn!("depth stream of {symbol} closed"),
+                    Err(e) => warn!("depth stream of {symbol} failed: {e}"),
+                },
+                result = consume => match result {
+                    Ok(ControlFlow::Break(())) => return,
+                    Ok(ControlFlow::Continue(())) => {}
+                    Err(e) => warn!("sync order book of {symbol} failed: {e}"),
+                },
+            }
+            warn!("reconnect depth stream after {RECONNECT_DELAY:?}");
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    fn apply_update(&mut self, update: Update) -> Result<()> {
+        match self.state {
+            State::Buffering => {
+                self.buffer.push(update);
+                return Ok(());
+            }
+            State::Snapshot(last_update_id) => {
+                if update.final_update_id < last_update_id {
+                    return Ok(());
+                }
+                if update.first_update_id > last_update_id {
+                    self.reset();
+                    return Err(Error::new(&format!(
+                        "depth updates of {} are missing after the snapshot {last_update_id}, \
+                         first update id: {}",
+                        self.symbol, update.first_update_id
+                    )));
+                }
+            }
+            State::Synced(last_update_id) => {
+                if update.prev_final_update_id != last_update_id {
+                    self.reset();
+                    return Err(Error::new(&format!(
+                        "depth updates of {} are missing, expected previous update id: \
+                         {last_update_id}, got: {}",
+                        self.symbol, update.prev_final_update_id
+                    )));
+                }
+            }
+        }
+        for (price, qty) in update.bids {
+            update_level(&mut self.bids, price, qty);
+        }
+        for (price, qty) in update.asks {
+            update_level(&mut self.asks, price, qty);
+        }
+        self.state = State::Synced(update.final_update_id);
+        Ok(())
+    }
+
+    /// Highest bid price and its quantity
+    pub fn best_bid(&self) -> Option<(Decimal, Decimal)> {
+        self.bids.last_key_value().map(|(p, q)| (*p, *q))
+    }
+
+    /// Lowest ask price and its quantity
+    pub fn best_ask(&self) -> Option<(Decimal, Decimal)> {
+        self.asks.first_key_value().map(|(p, q)| (*p, *q))
+    }
+
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()?.0 - self.best_bid()?.0)
+    }
+
+    /// Mid price weighted by the quantities of the best levels,
+    /// `(bid * askQty + ask * bidQty) / (bidQty + askQty)`
+    pub fn microprice(&self) -> Option<Decimal> {
+        let (bid, bid_qty) = self.best_bid()?;
+        let (ask, ask_qty) = self.best_ask()?;
+        (bid * ask_qty + ask * bid_qty).checked_div(bid_qty + ask_qty)
+    }
+
+    /// Average price of a market order of the quantity by walking the levels, buy orders take
+    /// asks and sell orders take bids. `None` if the book is not deep enough.
+    pub fn vwap_for_size(&self, side: OrderSide, qty: Decimal) -> Option<Decimal> {
+        let levels: Box<dyn Iterator<Item = (&Decimal, &Decimal)>> = match side {
+            OrderSide::Buy => Box::new(self.asks.iter()),
+            OrderSide::Sell => Box::new(self.bids.iter().rev()),
+        };
+        let mut remaining = qty;
+        let mut notional = Decimal::ZERO;
+        for (price, level_qty) in levels {
+            let filled = remaining.min(*level_qty);
+            notional += price * filled;
+            remaining -= filled;
+            if remaining.is_zero() {
+                return notional.checked_div(qty);
+            }
+        }
+        None
+    }
+
+    /// Quantity at the price of either side
+    pub fn depth_at(&self, price: Decimal) -> Option<Decimal> {
+        self.bids
+            .get(&price)
+            .or_else(|| self.asks.get(&price))
+            .copied()
+    }
+}
+
+/// Quantity of zero removes the level
+fn update_level(levels: &mut BTreeMap<Decimal, Decimal>, price: Decimal, qty: Decimal) {
+    if qty.is_zero() {
+        levels.remove(&price);
+    } else {
+        levels.insert(price, qty);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn levels(levels: &[(&str, &str)]) -> Vec<(Decimal, Decimal)> {
+        levels.iter().map(|(p, q)| (dec(p), dec(q))).collect()
+    }
+
+    fn snapshot(last_update_id: u64) -> response::OrderBook {
+        response::OrderBook {
+            last_update_id,
+            event_time: 0,
+            transaction_time: 0,
+            bids: levels(&[("99", "2"), ("98", "5")]),
+            asks: levels(&[("101", "1"), ("102", "4")]),
+        }
+    }
+
+    fn update(
+        first_update_id: u64,
+        final_update_id: u64,
+        prev_final_update_id: u64,
+        bids: &[(&str, &str)],
+        asks: &[(&str, &str)],
+    ) -> StreamItem {
+        StreamItem::DepthUpdate {
+            event_time: 0,
+            transaction_time: 0,
+            symbol: "BTCUSDT".to_string(),
+            first_update_id,
+            final_update_id,
+            prev_final_update_id,
+            bids: levels(bids),
+            asks: levels(asks),
+        }
+    }
+
+    #[test]
+    fn sync_buffered_updates() {
+        let mut book = LocalOrderBook::new("btcusdt");
+        // dropped, older than the snapshot
+        book.apply(update(90, 95, 89, &[("99", "0")], &[])).unwrap();
+        book.apply(update(96, 105, 95, &[("99", "3")], &[]))
+            .unwrap();
+        book.apply(update(106, 110, 105, &[], &[("101", "0")]))
+            .unwrap();
+        assert!(!book.is_synced());
+        book.set_snapshot(snapshot(100)).unwrap();
+        assert!(book.is_synced());
+        assert_eq!(book.best_bid(), Some((dec("99"), dec("3"))));
+        assert_eq!(book.best_ask(), Some((dec("102"), dec("4"))));
+        assert_eq!(book.spread(), Some(dec("3")));
+        assert_eq!(book.depth_at(dec("98")), Some(dec("5")));
+        assert_eq!(book.depth_at(dec("101")), None);
+
+        book.apply(update(111, 120, 110, &[("100", "1")], &[]))
+            .unwrap();
+        assert_eq!(book.best_bid(), Some((dec("100"), dec("1"))));
+    }
+
+    #[test]
+    fn ignore_other_symbols_and_events() {
+        let trade = || StreamItem::AggTrade {
+            event_time: 0,
+            symbol: "ETHUSDT".to_string(),
+            aggregate_trade_id: 1,
+            price: dec("100"),
+            quantity: dec("1"),
+            normal_quantity: dec("1"),
+            first_trade_id: 1,
+            last_trade_id: 1,
+            trade_time: 0,
+            is_buyer_market_maker: false,
+        };
+        let mut book = LocalOrderBook::new("ETHUSDT");
+        book.apply(trade()).unwrap();
+        assert!(!book.is_synced());
+        assert_eq!(book.best_bid(), None);
+
+        book.set_snapshot(snapshot(100)).unwrap();
+        book.apply(update(101, 110, 100, &[("99", "0")], &[]))
+            .unwrap();
+        book.apply(trade()).unwrap();
+        assert_eq!(book.best_bid(), Some((dec("99"), dec("2"))));
+        assert_eq!(book.best_ask(), Some((dec("101"), dec("1"))));
+        assert!(book.is_synced());
+    }
+
+    #[test]
+    fn stale_snapshot_keeps_buffer() {
+        let mut book = LocalOrderBook::new("BTCUSDT");
+        book.apply(update(106, 110, 105, &[("99", "3")], &[]))
+            .unwrap();
+        assert!(book.set_snapshot(snapshot(100)).is_err());
+        assert!(!book.is_synced());
+        book.apply(update(111, 120, 110, &[("98", "0")], &[]))
+            .unwrap();
+        book.set_snapshot(snapshot(108)).unwrap();
+        assert!(book.is_synced());
+        assert_eq!(book.best_bid(), Some((dec("99"), dec("3"))));
+        assert_eq!(book.depth_at(dec("98")), None);
+    }
+
+    #[test]
+    fn gap_resets_book() {
+        let mut book = LocalOrderBook::new("BTCUSDT");
+        book.set_snapshot(snapshot(100)).unwrap();
+        book.apply(update(95, 105, 94, &[], &[])).unwrap();
+        assert!(book.apply(update(110, 115, 108, &[], &[])).is_err());
+        assert!(!book.is_synced());
+        assert_eq!(book.best_bid(), None);
+
+        // the first update after the snapshot is missing
+        let mut book = LocalOrderBook::new("BTCUSDT");
+        book.set_snapshot(snapshot(100)).unwrap();
+        assert!(book.apply(update(105, 110, 104, &[], &[])).is_err());
+        assert!(!book.is_synced());
+    }
+
+    #[test]
+    fn microprice() {
+        let mut book = LocalOrderBook::new("BTCUSDT");
+        assert_eq!(book.microprice(), None);
+        book.set_snapshot(snapshot(100)).unwrap();
+        // (99 * 1 + 101 * 2) / (2 + 1)
+        assert_eq!(book.microprice(), Some(dec("301") / dec("3")));
+    }
+
+    #[test]
+    fn vwap_for_size() {
+        let mut book = LocalOrderBook::new("BTCUSDT");
+        book.set_snapshot(snapshot(100)).unwrap();
+        assert_eq!(
+            book.vwap_for_size(OrderSide::Buy, dec("0.5")),
+            Some(dec("101"))
+        );
+        // (101 * 1 + 102 * 1) / 2
+        assert_eq!(
+            book.vwap_for_size(OrderSide::Buy, dec("2")),
+            Some(dec("101.5"))
+        );
+        // (99 * 2 + 98 * 2) / 4
+        assert_eq!(
+            book.vwap_for_size(OrderSide::Sell, dec("4")),
+            Some(dec("98.5"))
+        );
+        assert_eq!(book.vwap_for_size(OrderSide::Sell, dec("8")), None);
+    }
+}
@@ -0,0 +1,111 @@
+//! Positions and balances kept by an account snapshot and the user data stream
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+
+use crate::{
+    error::Result,
+    usdm_futures::{
+        api::Client,
+        stream::response::StreamItem,
+        types::{PositionSide, response::AccountInformationV3},
+    },
+};
+
+/// Positions and wallet balances of the account kept in sync with the user data stream.
+///
+/// It's seeded from `account_information_v3`, then the amounts of `ACCOUNT_UPDATE` events
+/// replace the seeded ones. The user data stream should be received before seeding, so no
+/// event between them is missed, events older than the state of a position or balance are
+/// dropped. `ORDER_TRADE_UPDATE` events don't change the state, every fill is followed by an
+/// `ACCOUNT_UPDATE` with the new amounts, applying both would count the fill twice.
+#[derive(Debug, Default)]
+pub struct PositionTracker {
+    /// Amount and update time in milliseconds by symbol and position side
+    positions: HashMap<(String, PositionSide), (Decimal, i64)>,
+    /// Wallet balance and update time in milliseconds by asset
+    balances: HashMap<String, (Decimal, i64)>,
+}
+
+impl PositionTracker {
+    pub fn new(account: &AccountInformationV3) -> Self {
+        let positions = account
+            .positions
+            .iter()
+            .map(|p| {
+                (
+                    (p.symbol.clone(), p.position_side),
+                    (p.position_amt, p.update_time),
+                )
+            })
+            .collect();
+        let balances = account
+            .assets
+            .iter()
+            .map(|a| (a.asset.clone(), (a.wallet_balance, a.update_time)))
+            .collect();
+        PositionTracker {
+            positions,
+            balances,
+        }
+    }
+
+    /// Seed from the account information of the client
+    pub async fn seed(client: &Client) -> Result<Self> {
+        Ok(Self::new(&client.account_information_v3().await?))
+    }
+
+    /// Apply an event of the user data stream, other stream items are ignored
+    pub fn apply(&mut self, item: &StreamItem) {
+        let StreamItem::AccountUpdate {
+            transaction_time,
+            data,
+            ..
+        } = item
+        else {
+            return;
+        };
+        let time = *transaction_time;
+        for b in &data.balances {
+            let entry = self
+                .balances
+                .entry(b.asset.clone())
+                .or_insert((b.wallet_balance, time));
+            if entry.1 <= time {
+                *entry = (b.wallet_balance, time);
+            }
+        }
+        for p in &data.positions {
+            let entry = self
+                .positions
+                .entry((p.symbol.clone(), p.position_side))
+                .or_insert((p.position_amount, time));
+            if entry.1 <= time {
+                *entry = (p.position_amount, time);
+            }
+        }
+    }
+
+    /// Net position amount of the symbol, the sum of both sides in hedge mode, negative for
+    /// short
+    pub fn position(&self, symbol: &str) -> Decimal {
+        self.positions
+            .iter()
+            .filter(|((s, _), _)| s == symbol)
+            .map(|(_, (amount, _))| amount)
+            .sum()
+    }
+
+    /// Position amount of the symbol by the side, `Both` in one-way mode
+    pub fn position_by_side(&self, symbol: &str, side: PositionSide) -> Decimal {
+        self.positions
+            .get(&(symbol.to_string(), side))
+            .map_or(Decimal::ZERO, |(amount, _)| *amount)
+    }
+
+    /// Wallet balance of the asset, `None` if it's not in the account
+    pub fn balance(&self, asset: &str) -> Option<Decimal> {
+        self.balances.get(asset).map(|(balance, _)| *balance)
+    }
+}
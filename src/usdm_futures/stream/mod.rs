@@ -1,55 +1,93 @@
-use fluent_uri::Uri;
-use futures_channel::mpsc::UnboundedSender;
-use futures_util::{SinkExt, StreamExt};
-use tokio::net::TcpStream;
-use tokio_tungstenite::{client_async_tls, connect_async, tungstenite::Message};
-use tokio_util::compat::TokioAsyncReadCompatExt;
-use tracing::{error, info};
+use std::{
+    ops::ControlFlow,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 
-use crate::error::{Error, Result};
+use futures_channel::mpsc::{Sender, UnboundedSender};
+use futures_util::{SinkExt, future::try_join_all, stream::pending};
+use tokio_util::sync::CancellationToken;
 
+use crate::{
+    error::{Error, Result},
+    log::{error, warn},
+    stream::{Exit, IDLE_TIMEOUT, SentCommands, close, close_error, connect, drive, idle_error},
+};
+
+pub mod monitor;
 pub mod request;
 pub mod response;
 
-const URL: &str = "wss://fstream.binance.com/stream";
+const COMBINED_URL: &str = "wss://fstream.binance.com/stream";
+const RAW_URL: &str = "wss://fstream.binance.com/ws";
+/// Maximum number of streams of a single connection
+pub const MAX_STREAMS: usize = 200;
+/// Delay between connections of `receive_many`, to stay under the limit of subscribe messages
+const CONNECT_INTERVAL: Duration = Duration::from_millis(200);
+pub(crate) const RECONNECT_DELAY: Duration = Duration::from_secs(1);
 
-struct Socks5Proxy<'a> {
-    server: &'a str,
-    port: Option<u16>,
-    auth: Option<(&'a str, &'a str)>,
+/// Endpoint of the websocket connection
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StreamMode {
+    /// `/stream`, stream items are wrapped as `{"stream":"<streamName>","data":<rawPayload>}`
+    #[default]
+    Combined,
+    /// `/ws`, raw stream items without the stream name, for lower overhead of single stream
+    Raw,
 }
 
-impl<'a> Socks5Proxy<'a> {
-    async fn connect_to(&self, dest_addr: (&[u8], u16)) -> Result<TcpStream> {
-        let mut connect = tokio::net::TcpStream::connect((self.server, self.port.unwrap_or(1080)))
-            .await?
-            .compat();
-        socks5_client::connect(&mut connect, dest_addr.into(), self.auth.map(Into::into)).await?;
-        Ok(connect.into_inner())
+impl StreamMode {
+    fn url(&self) -> &'static str {
+        match self {
+            StreamMode::Combined => COMBINED_URL,
+            StreamMode::Raw => RAW_URL,
+        }
     }
 }
 
-impl<'a> TryFrom<Uri<&'a str>> for Socks5Proxy<'a> {
-    type Error = Error;
+/// What `receive_bounded` does when the channel is full
+#[derive(Debug, Clone)]
+pub enum Overflow {
+    /// Wait for the consumer, the server may close the connection if it's too slow
+    Wait,
+    /// Drop the incoming stream items and count them, items already in the channel can't be
+    /// taken back by the sender so the newest ones are dropped
+    DropNewest(Arc<AtomicU64>),
+}
+
+pub struct ReceiveOptions {
+    pub mode: StreamMode,
+    /// Check every stream item before sending it
+    pub monitor: monitor::Monitor,
+    /// Unsubscribe streams and close the connection when the token is cancelled
+    pub shutdown: Option<CancellationToken>,
+    /// Fail the connection if no message including pings is received in time, 10 minutes by
+    /// default, `None` to wait forever
+    pub idle_timeout: Option<Duration>,
+}
 
-    fn try_from(v: Uri<&'a str>) -> Result<Self> {
-        if v.scheme().as_str() != "socks5h" {
-            return Err(Error::new("invalid proxy scheme"));
+impl Default for ReceiveOptions {
+    fn default() -> Self {
+        ReceiveOptions {
+            mode: StreamMode::default(),
+            monitor: monitor::Monitor::default(),
+            shutdown: None,
+            idle_timeout: Some(IDLE_TIMEOUT),
         }
+    }
+}
 
-        let authority = v
-            .authority()
-            .ok_or_else(|| Error::new("invalid proxy url"))?;
-        let auth = match authority.userinfo() {
-            Some(u) => u.split_once(':').map(|(u, p)| (u.as_str(), p.as_str())),
-            None => None,
-        };
-        Ok(Socks5Proxy {
-            server: authority.host(),
-            port: authority.port_to_u16()?,
-            auth,
-        })
+pub(crate) fn check_stream_count(streams: &[request::Stream]) -> Result<()> {
+    if streams.len() > MAX_STREAMS {
+        return Err(Error::new(&format!(
+            "too many streams: {}, at most {MAX_STREAMS} streams per connection",
+            streams.len()
+        )));
     }
+    Ok(())
 }
 
 pub async fn receive(
@@ -57,43 +95,279 @@ pub async fn receive(
     tx: UnboundedSender<response::Stream>,
     proxy: Option<&str>,
 ) -> Result<()> {
-    let (mut stream, _) = match proxy {
-        Some(proxy) => {
-            let uri = Uri::parse(proxy)?;
-            let socks5_info: Socks5Proxy = uri.try_into()?;
-            let proxy = socks5_info
-                .connect_to(("fstream.binance.com".as_bytes(), 443))
-                .await?;
-            client_async_tls(URL, proxy).await?
+    receive_with_options(streams, tx, proxy, ReceiveOptions::default()).await
+}
+
+/// Same as `receive` with options.
+///
+/// In raw mode, the name of received streams is the subscribed stream if there is only one,
+/// otherwise it's empty since raw stream items carry no stream name.
+pub async fn receive_with_options(
+    streams: Vec<request::Stream>,
+    tx: UnboundedSender<response::Stream>,
+    proxy: Option<&str>,
+    options: ReceiveOptions,
+) -> Result<()> {
+    receive_into(streams, proxy, options, async |s| Ok(tx.unbounded_send(s)?)).await
+}
+
+/// Receive events of a user data stream, e.g. `ACCOUNT_UPDATE` and `ORDER_TRADE_UPDATE`.
+///
+/// The listen key is from `Client::start_user_data_stream`, it expires after 60 minutes unless
+/// it's kept alive by `Client::keepalive_user_data_stream`, the connection is closed by the
+/// server then. The name of received streams is the listen key.
+pub async fn receive_user_data(
+    listen_key: &str,
+    tx: UnboundedSender<response::Stream>,
+    proxy: Option<&str>,
+) -> Result<()> {
+    let mut stream = connect(&format!("{RAW_URL}/{listen_key}"), proxy).await?;
+    let mut sent = SentCommands::default();
+    let exit = drive::<response::Response, _, _, _>(
+        &mut stream,
+        &CancellationToken::new(),
+        Some(IDLE_TIMEOUT),
+        &mut sent,
+        pending::<request::Command>(),
+        async |_, items| {
+            tx.unbounded_send(response::Stream::new(listen_key, items))?;
+            Ok(ControlFlow::Continue(()))
+        },
+    )
+    .await?;
+    match exit {
+        Exit::Closed(Some(frame)) => Err(close_error(&frame)),
+        Exit::Idle(timeout) => Err(idle_error(timeout)),
+        _ => Ok(()),
+    }
+}
+
+/// Same as `receive_with_options`, but send to a bounded channel, a slow consumer either
+/// slows down reading of the connection or loses stream items by the overflow option.
+pub async fn receive_bounded(
+    streams: Vec<request::Stream>,
+    mut tx: Sender<response::Stream>,
+    proxy: Option<&str>,
+    options: ReceiveOptions,
+    overflow: Overflow,
+) -> Result<()> {
+    receive_into(streams, proxy, options, async |s| {
+        match &overflow {
+            Overflow::Wait => tx.send(s).await?,
+            Overflow::DropNewest(dropped) => match tx.try_send(s) {
+                Err(e) if e.is_full() => {
+                    let n = dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                    warn!("channel is full, dropped {n} stream items in total");
+                }
+                result => result?,
+            },
         }
-        None => connect_async(URL).await?,
+        Ok(())
+    })
+    .await
+}
+
+async fn receive_into(
+    streams: Vec<request::Stream>,
+    proxy: Option<&str>,
+    options: ReceiveOptions,
+    mut send: impl AsyncFnMut(response::Stream) -> Result<()>,
+) -> Result<()> {
+    check_stream_count(&streams)?;
+    let ReceiveOptions {
+        mode,
+        mut monitor,
+        shutdown,
+        idle_timeout,
+    } = options;
+    let shutdown = shutdown.unwrap_or_default();
+    let raw_name = match streams.as_slice() {
+        [s] => s.to_string(),
+        _ => String::new(),
     };
-    stream
-        .send(request::Command::Subscribe(streams).to_message(0)?)
+    let mut stream = connect(mode.url(), proxy).await?;
+    let mut sent = SentCommands::default();
+    sent.send(&mut stream, &request::Command::Subscribe(streams.clone()))
         .await?;
-    while let Some(msg) = stream.next().await {
-        match msg? {
-            Message::Text(msg) => match serde_json::from_str(&msg)? {
-                response::Response::Error { error, id } => {
-                    error!(
-                        "response error: id: {id}, code: {}, message: {}",
-                        error.code, error.msg
-                    );
-                    break;
-                }
-                response::Response::Result { result, id } => {
-                    info!("result: {result:?}, id: {id}");
-                }
-                response::Response::Stream { stream, data } => {
-                    tx.unbounded_send(response::Stream::new(&stream, data))?
+    let exit = drive::<response::Response, _, _, _>(
+        &mut stream,
+        &shutdown,
+        idle_timeout,
+        &mut sent,
+        pending::<request::Command>(),
+        async |name, items| {
+            let name = name.unwrap_or_else(|| raw_name.clone());
+            items.iter().for_each(|i| monitor.check(&name, i));
+            send(response::Stream::new(&name, items)).await?;
+            Ok(ControlFlow::Continue(()))
+        },
+    )
+    .await?;
+    match exit {
+        Exit::Shutdown => {
+            close(
+                &mut stream,
+                request::Command::Unsubscribe(streams).to_message(sent.next_id())?,
+            )
+            .await
+        }
+        Exit::Error {
+            code,
+            msg,
+            id,
+            command,
+        } => {
+            error!("response error: id: {id}, code: {code}, message: {msg}, command: {command:?}");
+            Err(Error::WsResponse { id, code, msg })
+        }
+        Exit::Closed(Some(frame)) => Err(close_error(&frame)),
+        Exit::Idle(timeout) => Err(idle_error(timeout)),
+        Exit::Closed(None) | Exit::Stopped => Ok(()),
+    }
+}
+
+/// Split streams into chunks of at most `MAX_STREAMS` streams, one chunk per connection
+fn chunk_streams(streams: &[request::Stream]) -> impl Iterator<Item = &[request::Stream]> {
+    streams.chunks(MAX_STREAMS)
+}
+
+/// Whether reconnecting can't help, i.e. the server rejected the request rather than the
+/// transport failed
+fn is_permanent(error: &Error) -> bool {
+    match error {
+        Error::WsResponse { .. } => true,
+        // protocol error, unsupported data, invalid payload, message too big
+        Error::WsClosed { code, .. } => matches!(code, 1002 | 1003 | 1007 | 1009),
+        _ => false,
+    }
+}
+
+/// Receive any number of streams, split into connections of at most `MAX_STREAMS` streams.
+///
+/// Items of all connections are sent to `tx`. Every connection reconnects on its own when it's
+/// closed or failed by the transport, until `tx` is closed. It returns the error and drops all
+/// connections if any is rejected by server, e.g. an error response to subscribing an invalid
+/// stream name.
+pub async fn receive_many(
+    streams: Vec<request::Stream>,
+    tx: UnboundedSender<response::Stream>,
+    proxy: Option<&str>,
+) -> Result<()> {
+    let connections = chunk_streams(&streams).enumerate().map(|(i, chunk)| {
+        let tx = tx.clone();
+        async move {
+            tokio::time::sleep(CONNECT_INTERVAL * i as u32).await;
+            loop {
+                let result =
+                    receive_with_options(chunk.to_vec(), tx.clone(), proxy, Default::default())
+                        .await;
+                if tx.is_closed() {
+                    return Ok(());
                 }
-                response::Response::Single { stream, data } => {
-                    tx.unbounded_send(response::Stream::new(&stream, vec![*data]))?
+                match result {
+                    Ok(()) => warn!("connection {i} closed, reconnect after {RECONNECT_DELAY:?}"),
+                    Err(e) if is_permanent(&e) => {
+                        error!("connection {i} rejected by server: {e}");
+                        return Err(e);
+                    }
+                    Err(e) => {
+                        warn!("connection {i} failed: {e}, reconnect after {RECONNECT_DELAY:?}")
+                    }
                 }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    });
+    try_join_all(connections).await?;
+    Ok(())
+}
+
+/// Subscribe streams and collect `n` stream items, then unsubscribe and close the connection
+pub async fn collect_n(
+    streams: Vec<request::Stream>,
+    n: usize,
+    proxy: Option<&str>,
+) -> Result<Vec<(String, response::StreamItem)>> {
+    check_stream_count(&streams)?;
+    let mut stream = connect(StreamMode::Combined.url(), proxy).await?;
+    let mut sent = SentCommands::default();
+    sent.send(&mut stream, &request::Command::Subscribe(streams.clone()))
+        .await?;
+    let mut items = Vec::with_capacity(n);
+    if n > 0 {
+        let exit = drive::<response::Response, _, _, _>(
+            &mut stream,
+            &CancellationToken::new(),
+            Some(IDLE_TIMEOUT),
+            &mut sent,
+            pending::<request::Command>(),
+            async |name, data| {
+                let name = name.unwrap_or_default();
+                items.extend(data.into_iter().map(|i| (name.clone(), i)));
+                Ok(if items.len() < n {
+                    ControlFlow::Continue(())
+                } else {
+                    ControlFlow::Break(())
+                })
             },
-            Message::Ping(payload) => stream.send(Message::Pong(payload)).await?,
-            x => error!("invalid message from server: {x:?}"),
+        )
+        .await?;
+        match exit {
+            Exit::Error { code, msg, id, .. } => return Err(Error::WsResponse { id, code, msg }),
+            Exit::Closed(Some(frame)) => return Err(close_error(&frame)),
+            Exit::Idle(timeout) => return Err(idle_error(timeout)),
+            Exit::Closed(None) => {
+                return Err(Error::new(
+                    "connection closed before collected enough items",
+                ));
+            }
+            Exit::Stopped | Exit::Shutdown => {}
         }
     }
-    Ok(())
+    items.truncate(n);
+    close(
+        &mut stream,
+        request::Command::Unsubscribe(streams).to_message(sent.next_id())?,
+    )
+    .await?;
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usdm_futures::types::Symbol;
+
+    #[test]
+    fn chunk_streams_by_max_streams() {
+        let streams: Vec<_> = (0..2 * MAX_STREAMS + 1)
+            .map(|i| request::Stream::AggregateTrade(Symbol::from(format!("S{i}USDT").as_str())))
+            .collect();
+        let chunks: Vec<_> = chunk_streams(&streams).collect();
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            [MAX_STREAMS, MAX_STREAMS, 1]
+        );
+        assert!(chunks.iter().all(|c| check_stream_count(c).is_ok()));
+        assert_eq!(chunks.concat(), streams);
+        assert_eq!(chunk_streams(&streams[..MAX_STREAMS]).count(), 1);
+        assert_eq!(chunk_streams(&[]).count(), 0);
+    }
+
+    #[test]
+    fn permanent_errors() {
+        assert!(is_permanent(&Error::WsResponse {
+            id: 1,
+            code: 2,
+            msg: "Invalid request".to_string(),
+        }));
+        let closed = |code| Error::WsClosed {
+            code,
+            reason: String::new(),
+        };
+        assert!(is_permanent(&closed(1007)));
+        assert!(!is_permanent(&closed(1001)));
+        assert!(!is_permanent(&closed(1006)));
+        assert!(!is_permanent(&idle_error(IDLE_TIMEOUT)));
+    }
 }
@@ -0,0 +1,86 @@
+use std::{collections::HashMap, time::Duration};
+
+use super::response::StreamItem;
+use crate::{log::warn, timestamp};
+
+/// Abnormal state of a stream detected by `Monitor`
+#[derive(Debug, Clone)]
+pub enum Alert {
+    /// Lag between the event time and local time exceeds the threshold
+    Lag { stream: String, lag: Duration },
+    /// Depth updates are missing, the local order book should be rebuilt from a snapshot
+    Gap {
+        stream: String,
+        expected: u64,
+        got: u64,
+    },
+}
+
+/// Check the lag of event time and the sequence of depth updates of received stream items.
+///
+/// Alerts are logged as warnings, or passed to the callback if it's set.
+#[derive(Default)]
+pub struct Monitor {
+    max_lag: Option<Duration>,
+    callback: Option<Box<dyn FnMut(Alert) + Send>>,
+    /// Last `u` of depth updates by the stream name and symbol, items of raw streams of
+    /// multiple symbols share an empty stream name
+    last_update_ids: HashMap<(String, String), u64>,
+}
+
+impl Monitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Alert when `now - event_time` exceeds `max_lag`
+    pub fn with_max_lag(mut self, max_lag: Duration) -> Self {
+        self.max_lag = Some(max_lag);
+        self
+    }
+
+    /// Handle alerts by the callback instead of logging them
+    pub fn with_callback<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(Alert) + Send + 'static,
+    {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    pub fn check(&mut self, stream: &str, item: &StreamItem) {
+        if let Some(max_lag) = self.max_lag {
+            let lag = timestamp::duration_between(item.event_time(), timestamp::now_millis());
+            if lag > max_lag {
+                self.alert(Alert::Lag {
+                    stream: stream.to_string(),
+                    lag,
+                });
+            }
+        }
+        if let StreamItem::DepthUpdate {
+            symbol,
+            final_update_id,
+            prev_final_update_id,
+            ..
+        } = item
+            && let Some(last) = self
+                .last_update_ids
+                .insert((stream.to_string(), symbol.clone()), *final_update_id)
+            && last != *prev_final_update_id
+        {
+            self.alert(Alert::Gap {
+                stream: stream.to_string(),
+                expected: last,
+                got: *prev_final_update_id,
+            });
+        }
+    }
+
+    fn alert(&mut self, alert: Alert) {
+        match self.callback.as_mut() {
+            Some(callback) => callback(alert),
+            None => warn!("stream alert: {alert:?}"),
+        }
+    }
+}
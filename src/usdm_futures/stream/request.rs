@@ -1,10 +1,14 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
 use serde::Serialize;
 use tokio_tungstenite::tungstenite::Message;
 
 use crate::{
-    error::Result,
+    error::{Error, Result},
+    stream::StreamCommand,
     usdm_futures::types::{ContractType, KlineInterval, Symbol},
 };
 
@@ -44,6 +48,12 @@ impl Command {
     }
 }
 
+impl StreamCommand for Command {
+    fn to_message(&self, id: u64) -> Result<Message> {
+        Command::to_message(self, id)
+    }
+}
+
 /*
 impl Display for ContractType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -57,18 +67,23 @@ impl Display for ContractType {
 }
 */
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Stream {
     /// Aggregate Trade Streams
     AggregateTrade(Symbol),
-    /// Mark Price Stream
-    MarkPrice(Symbol),
-    /// Mark Price Stream for All market
-    MarkPriceAllMarket,
-    /// Kline/Candlestick Streams
+    /// Mark Price Stream, update speed in seconds: 3(default) or 1
+    MarkPrice {
+        symbol: Symbol,
+        update_speed: Option<u16>,
+    },
+    /// Mark Price Stream for All market, update speed in seconds: 3(default) or 1
+    MarkPriceAllMarket { update_speed: Option<u16> },
+    /// Kline/Candlestick Streams, the open time of klines is in the timezone offset if set,
+    /// e.g. `+08:00`
     Kline {
         symbol: Symbol,
         interval: KlineInterval,
+        timezone: Option<String>,
     },
     /// Continuous Contract Kline/Candlestick Streams
     ContinuousContractKline {
@@ -84,24 +99,298 @@ pub enum Stream {
     IndividualSymbolTicker { symbol: Symbol },
     /// All Market Mini Tickers Stream
     AllMarketMiniTickers,
+    /// Composite Index Symbol Information Streams
+    CompositeIndex { symbol: Symbol },
+    /// Diff. Book Depth Streams, update speed in milliseconds: 250(default), 500 or 100
+    DiffBookDepth {
+        symbol: Symbol,
+        update_speed: Option<u16>,
+    },
+}
+
+impl Stream {
+    /// Aggregate trade streams of symbols
+    pub fn agg_trades<I, S>(symbols: I) -> Vec<Stream>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Symbol>,
+    {
+        symbols
+            .into_iter()
+            .map(|s| Stream::AggregateTrade(s.into()))
+            .collect()
+    }
+
+    /// Mark price streams of symbols
+    pub fn mark_prices<I, S>(symbols: I) -> Vec<Stream>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Symbol>,
+    {
+        symbols
+            .into_iter()
+            .map(|s| Stream::MarkPrice {
+                symbol: s.into(),
+                update_speed: None,
+            })
+            .collect()
+    }
+
+    /// Kline streams of symbols in the same interval
+    pub fn klines<I, S>(symbols: I, interval: KlineInterval) -> Vec<Stream>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Symbol>,
+    {
+        symbols
+            .into_iter()
+            .map(|s| Stream::Kline {
+                symbol: s.into(),
+                interval,
+                timezone: None,
+            })
+            .collect()
+    }
+
+    /// Individual symbol ticker streams of symbols
+    pub fn tickers<I, S>(symbols: I) -> Vec<Stream>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Symbol>,
+    {
+        symbols
+            .into_iter()
+            .map(|s| Stream::IndividualSymbolTicker { symbol: s.into() })
+            .collect()
+    }
+
+    /// Individual symbol mini ticker streams of symbols
+    pub fn mini_tickers<I, S>(symbols: I) -> Vec<Stream>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Symbol>,
+    {
+        symbols
+            .into_iter()
+            .map(|s| Stream::IndividualSymbolMiniTicker { symbol: s.into() })
+            .collect()
+    }
 }
 
 impl Display for Stream {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // stream names are lowercase, while symbols are kept in uppercase for the REST api
+        fn lower(symbol: &Symbol) -> String {
+            symbol.as_str().to_lowercase()
+        }
+
         match self {
-            Stream::AggregateTrade(s) => write!(f, "{s}@aggTrade"),
-            Stream::MarkPrice(s) => write!(f, "{s}@markPrice"),
-            Stream::MarkPriceAllMarket => write!(f, "!markPrice@arr"),
-            Stream::Kline { symbol, interval } => write!(f, "{symbol}@kline_{interval}>"),
+            Stream::AggregateTrade(s) => write!(f, "{}@aggTrade", lower(s)),
+            Stream::MarkPrice {
+                symbol,
+                update_speed,
+            } => match update_speed {
+                Some(speed) => write!(f, "{}@markPrice@{speed}s", lower(symbol)),
+                None => write!(f, "{}@markPrice", lower(symbol)),
+            },
+            Stream::MarkPriceAllMarket { update_speed } => match update_speed {
+                Some(speed) => write!(f, "!markPrice@arr@{speed}s"),
+                None => write!(f, "!markPrice@arr"),
+            },
+            Stream::Kline {
+                symbol,
+                interval,
+                timezone,
+            } => match timezone {
+                Some(timezone) => write!(f, "{}@kline_{interval}@{timezone}", lower(symbol)),
+                None => write!(f, "{}@kline_{interval}", lower(symbol)),
+            },
             Stream::ContinuousContractKline {
                 pair,
                 contract_type,
                 interval,
-            } => write!(f, "{pair}_{contract_type}@continuousKline_{interval}"),
-            Stream::IndividualSymbolMiniTicker { symbol } => write!(f, "{symbol}@miniTicker"),
+            } => write!(
+                f,
+                "{}_{}@continuousKline_{interval}",
+                pair.to_lowercase(),
+                contract_type.to_string().to_lowercase()
+            ),
+            Stream::IndividualSymbolMiniTicker { symbol } => {
+                write!(f, "{}@miniTicker", lower(symbol))
+            }
             Stream::AllMarketTickers => write!(f, "!ticker@arr"),
-            Stream::IndividualSymbolTicker { symbol } => write!(f, "{symbol}@ticker"),
+            Stream::IndividualSymbolTicker { symbol } => write!(f, "{}@ticker", lower(symbol)),
             Stream::AllMarketMiniTickers => write!(f, "!miniTicker@arr"),
+            Stream::CompositeIndex { symbol } => write!(f, "{}@compositeIndex", lower(symbol)),
+            Stream::DiffBookDepth {
+                symbol,
+                update_speed,
+            } => match update_speed {
+                Some(speed) => write!(f, "{}@depth@{speed}ms", lower(symbol)),
+                None => write!(f, "{}@depth", lower(symbol)),
+            },
+        }
+    }
+}
+
+/// Parse a stream name, e.g. from `ListSubscriptions`, symbols and pairs are uppercased like
+/// `Symbol`.
+impl FromStr for Stream {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::new(&format!("invalid stream name: {s}"));
+        let seconds = |i: &str| i.strip_suffix('s').and_then(|i| i.parse().ok());
+        match s {
+            "!markPrice@arr" => return Ok(Stream::MarkPriceAllMarket { update_speed: None }),
+            "!ticker@arr" => return Ok(Stream::AllMarketTickers),
+            "!miniTicker@arr" => return Ok(Stream::AllMarketMiniTickers),
+            _ => {}
+        }
+        if let Some(speed) = s.strip_prefix("!markPrice@arr@") {
+            return Ok(Stream::MarkPriceAllMarket {
+                update_speed: Some(seconds(speed).ok_or_else(invalid)?),
+            });
+        }
+        let (name, kind) = s.split_once('@').ok_or_else(invalid)?;
+        Ok(match kind {
+            "aggTrade" => Stream::AggregateTrade(name.parse()?),
+            "markPrice" => Stream::MarkPrice {
+                symbol: name.parse()?,
+                update_speed: None,
+            },
+            "miniTicker" => Stream::IndividualSymbolMiniTicker {
+                symbol: name.parse()?,
+            },
+            "ticker" => Stream::IndividualSymbolTicker {
+                symbol: name.parse()?,
+            },
+            "compositeIndex" => Stream::CompositeIndex {
+                symbol: name.parse()?,
+            },
+            "depth" => Stream::DiffBookDepth {
+                symbol: name.parse()?,
+                update_speed: None,
+            },
+            _ => {
+                if let Some(speed) = kind.strip_prefix("markPrice@") {
+                    Stream::MarkPrice {
+                        symbol: name.parse()?,
+                        update_speed: Some(seconds(speed).ok_or_else(invalid)?),
+                    }
+                } else if let Some(speed) = kind
+                    .strip_prefix("depth@")
+                    .and_then(|i| i.strip_suffix("ms"))
+                {
+                    Stream::DiffBookDepth {
+                        symbol: name.parse()?,
+                        update_speed: Some(speed.parse().map_err(|_| invalid())?),
+                    }
+                } else if let Some(interval) = kind.strip_prefix("kline_") {
+                    let (interval, timezone) = match interval.split_once('@') {
+                        Some((interval, timezone)) => (interval, Some(timezone.to_string())),
+                        None => (interval, None),
+                    };
+                    Stream::Kline {
+                        symbol: name.parse()?,
+                        interval: interval.parse()?,
+                        timezone,
+                    }
+                } else if let Some(interval) = kind.strip_prefix("continuousKline_") {
+                    let (pair, contract_type) = name.split_once('_').ok_or_else(invalid)?;
+                    let contract_type: ContractType = contract_type.to_uppercase().parse()?;
+                    Stream::ContinuousContractKline {
+                        pair: pair.to_uppercase(),
+                        contract_type,
+                        interval: interval.parse()?,
+                    }
+                } else {
+                    return Err(invalid());
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_name_round_trip() {
+        let symbol = || Symbol::from("BTCUSDT");
+        let mut streams = vec![
+            Stream::AggregateTrade(symbol()),
+            Stream::MarkPriceAllMarket { update_speed: None },
+            Stream::MarkPriceAllMarket {
+                update_speed: Some(1),
+            },
+            Stream::IndividualSymbolMiniTicker { symbol: symbol() },
+            Stream::AllMarketTickers,
+            Stream::IndividualSymbolTicker { symbol: symbol() },
+            Stream::AllMarketMiniTickers,
+            Stream::CompositeIndex { symbol: symbol() },
+        ];
+        for update_speed in [None, Some(1), Some(3)] {
+            streams.push(Stream::MarkPrice {
+                symbol: symbol(),
+                update_speed,
+            });
+        }
+        for update_speed in [None, Some(100), Some(250), Some(500)] {
+            streams.push(Stream::DiffBookDepth {
+                symbol: symbol(),
+                update_speed,
+            });
+        }
+        for interval in [KlineInterval::I1m, KlineInterval::I4h, KlineInterval::I1M] {
+            for timezone in [None, Some("+08:00"), Some("-05:30")] {
+                streams.push(Stream::Kline {
+                    symbol: symbol(),
+                    interval,
+                    timezone: timezone.map(|i| i.to_string()),
+                });
+            }
+        }
+        for contract_type in [
+            ContractType::Perpetual,
+            ContractType::CurrentQuarter,
+            ContractType::NextQuarter,
+            ContractType::TradifiPerpetual,
+        ] {
+            streams.push(Stream::ContinuousContractKline {
+                pair: "BTCUSDT".to_string(),
+                contract_type,
+                interval: KlineInterval::I1d,
+            });
+        }
+        for s in streams {
+            assert_eq!(s.to_string().parse::<Stream>().unwrap(), s, "{s}");
+        }
+    }
+
+    #[test]
+    fn parse_stream_name() {
+        assert_eq!(
+            "btcusdt_perpetual@continuousKline_1m"
+                .parse::<Stream>()
+                .unwrap(),
+            Stream::ContinuousContractKline {
+                pair: "BTCUSDT".to_string(),
+                contract_type: ContractType::Perpetual,
+                interval: KlineInterval::I1m,
+            }
+        );
+        for name in [
+            "btcusdt",
+            "btcusdt@unknown",
+            "btcusdt@depth@fast",
+            "btcusdt@markPrice@1",
+            "btcusdt@kline_7m",
+            "btcusdt_unknown@continuousKline_1m",
+            "!markPrice@arr@1ms",
+        ] {
+            assert!(name.parse::<Stream>().is_err(), "{name}");
         }
     }
 }
@@ -1,8 +1,18 @@
-use chrono::{DateTime, Utc, serde::ts_milliseconds};
+use std::time::Duration;
+
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    stream::{Decode, Frame},
+    timestamp,
+    usdm_futures::types::{
+        OrderSide, OrderStatus, OrderType, PositionSide, PriceMatch, SelfTradePreventionMode,
+        TimeInForce, WorkingType, response::KlineCandlestickData,
+    },
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Response {
     Error {
@@ -21,9 +31,42 @@ pub enum Response {
         stream: String,
         data: Box<StreamItem>,
     },
+    /// Stream item of the raw stream endpoint
+    Raw(Box<StreamItem>),
+}
+
+impl Decode for Response {
+    type Item = StreamItem;
+
+    fn into_frame(self) -> Frame<StreamItem> {
+        match self {
+            Response::Error { error, id } => Frame::Error {
+                code: error.code,
+                msg: error.msg,
+                id,
+            },
+            Response::Result { result, id } => Frame::Result {
+                result: result.map(serde_json::Value::String),
+                id,
+            },
+            Response::Stream { stream, data } => Frame::Items {
+                stream: Some(stream),
+                items: data,
+            },
+            Response::Single { stream, data } => Frame::Items {
+                stream: Some(stream),
+                items: vec![*data],
+            },
+            Response::Raw(data) => Frame::Items {
+                stream: None,
+                items: vec![*data],
+            },
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ErrorDetail {
     pub code: u64,
     pub msg: String,
@@ -43,14 +86,15 @@ impl Stream {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "e")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub enum StreamItem {
     #[serde(rename = "aggTrade")]
     AggTrade {
         /// Event time
-        #[serde(rename = "E", with = "ts_milliseconds")]
-        event_time: DateTime<Utc>,
+        #[serde(rename = "E")]
+        event_time: i64,
         /// Symbol
         #[serde(rename = "s")]
         symbol: String,
@@ -73,8 +117,8 @@ pub enum StreamItem {
         #[serde(rename = "l")]
         last_trade_id: u64,
         /// Trade time
-        #[serde(rename = "T", with = "ts_milliseconds")]
-        trade_time: DateTime<Utc>,
+        #[serde(rename = "T")]
+        trade_time: i64,
         /// Is the buyer the market maker?
         #[serde(rename = "m")]
         is_buyer_market_maker: bool,
@@ -82,8 +126,8 @@ pub enum StreamItem {
     #[serde(rename = "markPriceUpdate")]
     MarkPriceUpdate {
         /// Event time
-        #[serde(rename = "E", with = "ts_milliseconds")]
-        event_time: DateTime<Utc>,
+        #[serde(rename = "E")]
+        event_time: i64,
         /// Symbol
         #[serde(rename = "s")]
         symbol: String,
@@ -100,14 +144,14 @@ pub enum StreamItem {
         #[serde(rename = "r")]
         funding_rate: Decimal,
         /// next funding time
-        #[serde(rename = "T", with = "ts_milliseconds")]
-        next_funding_time: DateTime<Utc>,
+        #[serde(rename = "T")]
+        next_funding_time: i64,
     },
     #[serde(rename = "continuous_kline")]
     ContinuousKline {
         /// Event time
-        #[serde(rename = "E", with = "ts_milliseconds")]
-        event_time: DateTime<Utc>,
+        #[serde(rename = "E")]
+        event_time: i64,
         /// Pair
         #[serde(rename = "ps")]
         symbol: String,
@@ -121,8 +165,8 @@ pub enum StreamItem {
     #[serde(rename = "24hrMiniTicker")]
     E24hrMiniTicker {
         /// Event time
-        #[serde(rename = "E", with = "ts_milliseconds")]
-        event_time: DateTime<Utc>,
+        #[serde(rename = "E")]
+        event_time: i64,
         /// Symbol
         #[serde(rename = "s")]
         symbol: String,
@@ -148,8 +192,8 @@ pub enum StreamItem {
     #[serde(rename = "24hrTicker")]
     E24hrTicker {
         /// Event time
-        #[serde(rename = "E", with = "ts_milliseconds")]
-        event_time: DateTime<Utc>,
+        #[serde(rename = "E")]
+        event_time: i64,
         /// Symbol
         #[serde(rename = "s")]
         symbol: String,
@@ -184,11 +228,11 @@ pub enum StreamItem {
         #[serde(rename = "q")]
         quote_asset_volume: Decimal,
         /// Statistics open time
-        #[serde(rename = "O", with = "ts_milliseconds")]
-        statistics_open_time: DateTime<Utc>,
+        #[serde(rename = "O")]
+        statistics_open_time: i64,
         /// Statistics close time
-        #[serde(rename = "C", with = "ts_milliseconds")]
-        statistics_close_time: DateTime<Utc>,
+        #[serde(rename = "C")]
+        statistics_close_time: i64,
         /// First trade ID
         #[serde(rename = "F")]
         first_trade_id: u64,
@@ -199,16 +243,386 @@ pub enum StreamItem {
         #[serde(rename = "n")]
         total_number_of_trades: u64,
     },
+    #[serde(rename = "depthUpdate")]
+    DepthUpdate {
+        /// Event time
+        #[serde(rename = "E")]
+        event_time: i64,
+        /// Transaction time
+        #[serde(rename = "T")]
+        transaction_time: i64,
+        /// Symbol
+        #[serde(rename = "s")]
+        symbol: String,
+        /// First update ID in event
+        #[serde(rename = "U")]
+        first_update_id: u64,
+        /// Final update ID in event
+        #[serde(rename = "u")]
+        final_update_id: u64,
+        /// Final update Id in last stream(ie `u` in last stream)
+        #[serde(rename = "pu")]
+        prev_final_update_id: u64,
+        /// Bids to be updated, price and quantity
+        #[serde(rename = "b")]
+        bids: Vec<(Decimal, Decimal)>,
+        /// Asks to be updated, price and quantity
+        #[serde(rename = "a")]
+        asks: Vec<(Decimal, Decimal)>,
+    },
+    #[serde(rename = "compositeIndex")]
+    CompositeIndex {
+        /// Event time
+        #[serde(rename = "E")]
+        event_time: i64,
+        /// Symbol
+        #[serde(rename = "s")]
+        symbol: String,
+        /// Index price
+        #[serde(rename = "p")]
+        price: Decimal,
+        /// Component type, e.g. `baseAsset`
+        #[serde(rename = "C")]
+        component: String,
+        /// Composition of the index
+        #[serde(rename = "c")]
+        composition: Vec<CompositeIndexComponent>,
+    },
+    /// Balance and position update of the user data stream
+    #[serde(rename = "ACCOUNT_UPDATE")]
+    AccountUpdate {
+        /// Event time
+        #[serde(rename = "E")]
+        event_time: i64,
+        /// Transaction time
+        #[serde(rename = "T")]
+        transaction_time: i64,
+        #[serde(rename = "a")]
+        data: AccountUpdateData,
+    },
+    /// Order update of the user data stream
+    #[serde(rename = "ORDER_TRADE_UPDATE")]
+    OrderTradeUpdate {
+        /// Event time
+        #[serde(rename = "E")]
+        event_time: i64,
+        /// Transaction time
+        #[serde(rename = "T")]
+        transaction_time: i64,
+        #[serde(rename = "o")]
+        order: Box<OrderTradeUpdateData>,
+    },
+    /// Events which are not known yet, kept as raw json
+    #[serde(untagged, deserialize_with = "unknown_event")]
+    Unknown(serde_json::Value),
+}
+
+/// Event types of the variants, events of them are not taken as unknown
+const EVENTS: &[&str] = &[
+    "aggTrade",
+    "markPriceUpdate",
+    "continuous_kline",
+    "24hrMiniTicker",
+    "24hrTicker",
+    "depthUpdate",
+    "compositeIndex",
+    "ACCOUNT_UPDATE",
+    "ORDER_TRADE_UPDATE",
+];
+
+fn unknown_event<'de, D>(deserializer: D) -> Result<serde_json::Value, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    crate::stream::unknown_event(deserializer, EVENTS)
+}
+
+/// Fields of enum variants are public as the enum, the accessors are for the fields shared by
+/// all variants.
+impl StreamItem {
+    /// Symbol, or pair of continuous contract kline
+    pub fn symbol(&self) -> &str {
+        match self {
+            StreamItem::AggTrade { symbol, .. }
+            | StreamItem::MarkPriceUpdate { symbol, .. }
+            | StreamItem::ContinuousKline { symbol, .. }
+            | StreamItem::E24hrMiniTicker { symbol, .. }
+            | StreamItem::E24hrTicker { symbol, .. }
+            | StreamItem::DepthUpdate { symbol, .. }
+            | StreamItem::CompositeIndex { symbol, .. } => symbol,
+            StreamItem::OrderTradeUpdate { order, .. } => &order.symbol,
+            StreamItem::AccountUpdate { .. } => "",
+            StreamItem::Unknown(v) => v.get("s").and_then(|s| s.as_str()).unwrap_or_default(),
+        }
+    }
+
+    /// Event time of the item in milliseconds, or the current time if an unknown event carries no
+    /// event time
+    pub fn event_time(&self) -> i64 {
+        match self {
+            StreamItem::AggTrade { event_time, .. }
+            | StreamItem::MarkPriceUpdate { event_time, .. }
+            | StreamItem::ContinuousKline { event_time, .. }
+            | StreamItem::E24hrMiniTicker { event_time, .. }
+            | StreamItem::E24hrTicker { event_time, .. }
+            | StreamItem::DepthUpdate { event_time, .. }
+            | StreamItem::CompositeIndex { event_time, .. }
+            | StreamItem::AccountUpdate { event_time, .. }
+            | StreamItem::OrderTradeUpdate { event_time, .. } => *event_time,
+            StreamItem::Unknown(v) => v
+                .get("E")
+                .and_then(|t| t.as_i64())
+                .unwrap_or_else(timestamp::now_millis),
+        }
+    }
+
+    /// Funding info of a mark price update
+    pub fn funding_info(&self) -> Option<FundingInfo> {
+        match self {
+            StreamItem::MarkPriceUpdate {
+                symbol,
+                mark_price,
+                funding_rate,
+                next_funding_time,
+                ..
+            } => Some(FundingInfo {
+                symbol: symbol.clone(),
+                mark_price: *mark_price,
+                funding_rate: *funding_rate,
+                next_funding_time: *next_funding_time,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Data of `ACCOUNT_UPDATE`, only the changed balances and positions are included
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct AccountUpdateData {
+    /// Event reason type, e.g. `ORDER`, `FUNDING_FEE`
+    #[serde(rename = "m")]
+    pub reason: String,
+    #[serde(rename = "B")]
+    pub balances: Vec<AccountUpdateBalance>,
+    #[serde(rename = "P")]
+    pub positions: Vec<AccountUpdatePosition>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct AccountUpdateBalance {
+    /// Asset
+    #[serde(rename = "a")]
+    pub asset: String,
+    /// Wallet balance
+    #[serde(rename = "wb")]
+    pub wallet_balance: Decimal,
+    /// Cross wallet balance
+    #[serde(rename = "cw")]
+    pub cross_wallet_balance: Decimal,
+    /// Balance change except PnL and commission
+    #[serde(rename = "bc")]
+    pub balance_change: Decimal,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct AccountUpdatePosition {
+    /// Symbol
+    #[serde(rename = "s")]
+    pub symbol: String,
+    /// Position amount
+    #[serde(rename = "pa")]
+    pub position_amount: Decimal,
+    /// Entry price
+    #[serde(rename = "ep")]
+    pub entry_price: Decimal,
+    /// Breakeven price
+    #[serde(rename = "bep")]
+    pub breakeven_price: Decimal,
+    /// Accumulated realized pnl
+    #[serde(rename = "cr")]
+    pub accumulated_realized: Decimal,
+    /// Unrealized pnl
+    #[serde(rename = "up")]
+    pub unrealized_pnl: Decimal,
+    /// Margin type, `isolated` or `cross`
+    #[serde(rename = "mt")]
+    pub margin_type: String,
+    /// Isolated wallet, if isolated position
+    #[serde(rename = "iw")]
+    pub isolated_wallet: Decimal,
+    /// Position side
+    #[serde(rename = "ps")]
+    pub position_side: PositionSide,
+}
+
+/// Order of `ORDER_TRADE_UPDATE`
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct OrderTradeUpdateData {
+    /// Symbol
+    #[serde(rename = "s")]
+    pub symbol: String,
+    /// Client order id
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+    /// Side
+    #[serde(rename = "S")]
+    pub side: OrderSide,
+    /// Order type
+    #[serde(rename = "o")]
+    pub order_type: OrderType,
+    /// Time in force
+    #[serde(rename = "f")]
+    pub time_in_force: TimeInForce,
+    /// Original quantity
+    #[serde(rename = "q")]
+    pub quantity: Decimal,
+    /// Original price
+    #[serde(rename = "p")]
+    pub price: Decimal,
+    /// Average price
+    #[serde(rename = "ap")]
+    pub average_price: Decimal,
+    /// Stop price
+    #[serde(rename = "sp")]
+    pub stop_price: Decimal,
+    /// Execution type, e.g. `NEW`, `TRADE`, `CANCELED`
+    #[serde(rename = "x")]
+    pub execution_type: String,
+    /// Order status
+    #[serde(rename = "X")]
+    pub order_status: OrderStatus,
+    /// Order id
+    #[serde(rename = "i")]
+    pub order_id: u64,
+    /// Last filled quantity
+    #[serde(rename = "l")]
+    pub last_filled_quantity: Decimal,
+    /// Filled accumulated quantity
+    #[serde(rename = "z")]
+    pub filled_accumulated_quantity: Decimal,
+    /// Last filled price
+    #[serde(rename = "L")]
+    pub last_filled_price: Decimal,
+    /// Commission asset, only sent with commission
+    #[serde(rename = "N")]
+    pub commission_asset: Option<String>,
+    /// Commission, only sent with commission
+    #[serde(rename = "n")]
+    pub commission: Option<Decimal>,
+    /// Order trade time
+    #[serde(rename = "T")]
+    pub trade_time: i64,
+    /// Trade id
+    #[serde(rename = "t")]
+    pub trade_id: i64,
+    /// Bids notional
+    #[serde(rename = "b")]
+    pub bids_notional: Decimal,
+    /// Ask notional
+    #[serde(rename = "a")]
+    pub asks_notional: Decimal,
+    /// Is this trade the maker side?
+    #[serde(rename = "m")]
+    pub is_maker: bool,
+    /// Is this reduce only
+    #[serde(rename = "R")]
+    pub is_reduce_only: bool,
+    /// Stop price working type
+    #[serde(rename = "wt")]
+    pub working_type: WorkingType,
+    /// Original order type
+    #[serde(rename = "ot")]
+    pub original_order_type: OrderType,
+    /// Position side
+    #[serde(rename = "ps")]
+    pub position_side: PositionSide,
+    /// If close-all, pushed with conditional order
+    #[serde(rename = "cp")]
+    pub close_position: bool,
+    /// Activation price, only pushed with `TRAILING_STOP_MARKET` order
+    #[serde(rename = "AP")]
+    pub activation_price: Option<Decimal>,
+    /// Callback rate, only pushed with `TRAILING_STOP_MARKET` order
+    #[serde(rename = "cr")]
+    pub callback_rate: Option<Decimal>,
+    /// If price protection is turned on
+    #[serde(rename = "pP")]
+    pub price_protect: bool,
+    #[serde(rename = "si")]
+    pub ignore_si: i64,
+    #[serde(rename = "ss")]
+    pub ignore_ss: i64,
+    /// Realized profit of the trade
+    #[serde(rename = "rp")]
+    pub realized_profit: Decimal,
+    /// Self trade prevention mode
+    #[serde(rename = "V")]
+    pub self_trade_prevention_mode: SelfTradePreventionMode,
+    /// Price match mode
+    #[serde(rename = "pm")]
+    pub price_match: PriceMatch,
+    /// Auto cancel time of `GTD` order
+    #[serde(rename = "gtd")]
+    pub good_till_date: i64,
+}
+
+/// Component of `compositeIndex`
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct CompositeIndexComponent {
+    /// Base asset
+    #[serde(rename = "b")]
+    pub base_asset: String,
+    /// Quote asset
+    #[serde(rename = "q")]
+    pub quote_asset: String,
+    /// Weight in quantity
+    #[serde(rename = "w")]
+    pub weight_in_quantity: Decimal,
+    /// Weight in percentage
+    #[serde(rename = "W")]
+    pub weight_in_percentage: Decimal,
+    /// Index price of the component
+    #[serde(rename = "i")]
+    pub index_price: Decimal,
+}
+
+/// Funding fields of `markPriceUpdate`
+#[derive(Debug, Clone)]
+pub struct FundingInfo {
+    pub symbol: String,
+    pub mark_price: Decimal,
+    pub funding_rate: Decimal,
+    pub next_funding_time: i64,
+}
+
+impl FundingInfo {
+    /// Time until the next funding from `now` in milliseconds, zero if it's passed
+    pub fn time_until_funding(&self, now: i64) -> Duration {
+        timestamp::duration_between(now, self.next_funding_time)
+    }
+
+    /// Funding payment of a position at the current rate, the notional is positive for long and
+    /// negative for short. A positive payment is paid by the position, a negative one is
+    /// received.
+    pub fn projected_funding_payment(&self, position_notional: Decimal) -> Decimal {
+        position_notional * self.funding_rate
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Kline {
     /// Kline start time
-    #[serde(rename = "t", with = "ts_milliseconds")]
-    pub start_time: DateTime<Utc>,
+    #[serde(rename = "t")]
+    pub start_time: i64,
     /// Kline close time
-    #[serde(rename = "T", with = "ts_milliseconds")]
-    pub close_time: DateTime<Utc>,
+    #[serde(rename = "T")]
+    pub close_time: i64,
     /// Interval
     #[serde(rename = "i")]
     pub internal: String,
@@ -249,3 +663,100 @@ pub struct Kline {
     #[serde(rename = "Q")]
     pub taker_buy_quote_asset_volume: Decimal,
 }
+
+impl From<Kline> for KlineCandlestickData {
+    fn from(value: Kline) -> Self {
+        KlineCandlestickData {
+            open: value.open_price,
+            high: value.high_price,
+            low: value.low_price,
+            close: value.close_price,
+            volume: value.volume,
+            quote_asset_volume: value.quote_asset_volume,
+            number_of_trades: value.number_of_trades,
+            taker_buy_base_asset_volume: value.taker_buy_volume,
+            taker_buy_quote_asset_volume: value.taker_buy_quote_asset_volume,
+            open_time: value.start_time,
+            close_time: value.close_time,
+        }
+    }
+}
+
+/// The interval and update ids are not in the rest klines, they are left empty and 0. The kline
+/// is not known to be closed without the current time, `is_closed` is false, use
+/// `Kline::from_rest` to set it.
+impl From<KlineCandlestickData> for Kline {
+    fn from(value: KlineCandlestickData) -> Self {
+        Kline {
+            start_time: value.open_time,
+            close_time: value.close_time,
+            internal: String::new(),
+            first_update_id: 0,
+            last_update_id: 0,
+            open_price: value.open,
+            close_price: value.close,
+            high_price: value.high,
+            low_price: value.low,
+            volume: value.volume,
+            number_of_trades: value.number_of_trades,
+            is_closed: false,
+            quote_asset_volume: value.quote_asset_volume,
+            taker_buy_volume: value.taker_buy_base_asset_volume,
+            taker_buy_quote_asset_volume: value.taker_buy_quote_asset_volume,
+        }
+    }
+}
+
+impl Kline {
+    /// Kline of the rest api, it's closed if its close time is before `now` in milliseconds,
+    /// e.g. `timestamp::now_millis()`
+    pub fn from_rest(value: KlineCandlestickData, now: i64) -> Self {
+        let is_closed = value.close_time < now;
+        Kline {
+            is_closed,
+            ..value.into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rest_kline() -> KlineCandlestickData {
+        KlineCandlestickData {
+            open: "100".parse().unwrap(),
+            high: "110".parse().unwrap(),
+            low: "95".parse().unwrap(),
+            close: "105".parse().unwrap(),
+            volume: "12.5".parse().unwrap(),
+            quote_asset_volume: "1300".parse().unwrap(),
+            number_of_trades: 42,
+            taker_buy_base_asset_volume: "6".parse().unwrap(),
+            taker_buy_quote_asset_volume: "620".parse().unwrap(),
+            open_time: 60_000,
+            close_time: 119_999,
+        }
+    }
+
+    #[test]
+    fn kline_from_rest() {
+        let kline = Kline::from(rest_kline());
+        assert!(!kline.is_closed);
+        assert_eq!(kline.start_time, 60_000);
+        assert_eq!(kline.number_of_trades, 42);
+        assert!(!Kline::from_rest(rest_kline(), 119_999).is_closed);
+        assert!(Kline::from_rest(rest_kline(), 120_000).is_closed);
+
+        let data = KlineCandlestickData::from(Kline::from_rest(rest_kline(), 120_000));
+        let expected = rest_kline();
+        assert_eq!(data.open_time, expected.open_time);
+        assert_eq!(data.close_time, expected.close_time);
+        assert_eq!(data.close, expected.close);
+        assert_eq!(data.number_of_trades, expected.number_of_trades);
+        assert_eq!(
+            data.taker_buy_base_asset_volume,
+            expected.taker_buy_base_asset_volume
+        );
+    }
+}
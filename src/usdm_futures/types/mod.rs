@@ -1,6 +1,5 @@
-use std::{fmt, str::FromStr};
+use std::{fmt, str::FromStr, time::Duration};
 
-use chrono::{DateTime, Duration, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -9,30 +8,68 @@ use crate::error::{Error, Result};
 pub mod request;
 pub mod response;
 
-macro_rules! impl_enum_str {
-    ($t:ty) => {
-        impl fmt::Display for $t {
-            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                let s = serde_json::to_string(self).unwrap_or_default();
-                let s = s.trim_matches('"');
-                write!(f, "{s}")
-            }
-        }
+/// Trading symbol, always kept in uppercase as required by the REST api.
+///
+/// `FromStr` validates the symbol against `^[A-Z0-9_]{2,}$` after uppercasing, `_` is used by
+/// delivery contracts like `BTCUSDT_250328` and `BTCUSD_PERP`. The `From` conversions only
+/// uppercase for ergonomics.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Symbol(String);
+
+impl Symbol {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 
-        impl FromStr for $t {
-            type Err = Error;
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
 
-            fn from_str(s: &str) -> Result<Self> {
-                let s = format!("\"{s}\"");
-                Ok(serde_json::from_str(&s)?)
-            }
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Symbol {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.to_uppercase();
+        if s.len() < 2 || !s.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_') {
+            return Err(Error::new(&format!("invalid symbol: {s}")));
         }
-    };
+        Ok(Symbol(s))
+    }
 }
 
-pub type Symbol = String;
+impl From<&str> for Symbol {
+    fn from(value: &str) -> Self {
+        Symbol(value.to_uppercase())
+    }
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl From<String> for Symbol {
+    fn from(value: String) -> Self {
+        Symbol(value.to_uppercase())
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ContractType {
     Perpetual,
@@ -44,8 +81,11 @@ pub enum ContractType {
     #[serde(rename = "CURRENT_QUARTER DELIVERING")]
     CurrentQuarterDelivering,
     TradifiPerpetual,
+    /// Values which are not known yet
+    #[serde(other)]
+    Unknown,
 }
-impl_enum_str!(ContractType);
+impl_enum_str!(ContractType, ContractType::Unknown);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -58,8 +98,11 @@ pub enum ContractStatus {
     PreSettle,
     Settling,
     Close,
+    /// Values which are not known yet
+    #[serde(other)]
+    Unknown,
 }
-impl_enum_str!(ContractStatus);
+impl_enum_str!(ContractStatus, ContractStatus::Unknown);
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -71,13 +114,28 @@ pub enum OrderStatus {
     Rejected,
     Expired,
     ExpiredInMatch,
+    /// Values which are not known yet
+    #[serde(other)]
+    Unknown,
 }
-impl_enum_str!(OrderStatus);
+impl_enum_str!(OrderStatus, OrderStatus::Unknown);
 
 impl OrderStatus {
     pub fn is_open(&self) -> bool {
         matches!(self, OrderStatus::New | OrderStatus::PartiallyFilled)
     }
+
+    /// The order will not change anymore
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Canceled
+                | OrderStatus::Rejected
+                | OrderStatus::Expired
+                | OrderStatus::ExpiredInMatch
+        )
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
@@ -91,8 +149,11 @@ pub enum OrderType {
     TakeProfit,
     TakeProfitMarket,
     TrailingStopMarket,
+    /// Values which are not known yet
+    #[serde(other)]
+    Unknown,
 }
-impl_enum_str!(OrderType);
+impl_enum_str!(OrderType, OrderType::Unknown);
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -109,13 +170,16 @@ impl OrderSide {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PositionSide {
+    /// One-way mode
+    #[default]
     Both,
     Long,
     Short,
 }
+impl_enum_str!(PositionSide);
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -130,19 +194,21 @@ pub enum TimeInForce {
 }
 impl_enum_str!(TimeInForce);
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum WorkingType {
     MarkPrice,
     ContractPrice,
 }
+impl_enum_str!(WorkingType);
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum NewOrderRespType {
     Ack,
     Result,
 }
+impl_enum_str!(NewOrderRespType);
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum KlineInterval {
@@ -181,41 +247,66 @@ impl_enum_str!(KlineInterval);
 
 impl KlineInterval {
     pub fn num_seconds(&self) -> i64 {
-        let interval: Duration = (*self).into();
-        interval.num_seconds()
+        Duration::from(*self).as_secs() as i64
     }
 
-    pub fn get_start_time(&self, time: DateTime<Utc>) -> DateTime<Utc> {
-        let seconds = self.num_seconds();
-        let truncated_ts = (time.timestamp() / seconds) * seconds;
-        DateTime::from_timestamp(truncated_ts, 0).unwrap_or_default()
+    /// Open time of the kline containing the timestamp in milliseconds, klines of `1w` are
+    /// opened on Monday and klines of `1M` on the first day of the month, in UTC
+    pub fn get_start_time(&self, time: i64) -> i64 {
+        let day = (DAY * 1000) as i64;
+        match self {
+            // the unix epoch is on Thursday, 4 days after Monday
+            KlineInterval::I1w => (time - 4 * day).div_euclid(7 * day) * 7 * day + 4 * day,
+            KlineInterval::I1M => month_start(time.div_euclid(day)) * day,
+            _ => {
+                let millis = self.num_seconds() * 1000;
+                time.div_euclid(millis) * millis
+            }
+        }
     }
 
-    pub fn get_previous_time(&self, time: DateTime<Utc>) -> DateTime<Utc> {
-        let seconds = self.num_seconds() / 2;
-        let truncated_ts = (time.timestamp() / seconds) * seconds;
-        DateTime::from_timestamp(truncated_ts, 0).unwrap_or_default()
+    pub fn get_previous_time(&self, time: i64) -> i64 {
+        let millis = self.num_seconds() / 2 * 1000;
+        time.div_euclid(millis) * millis
     }
 }
 
+/// First day of the month of the days since the unix epoch, by the `civil_from_days` algorithm of
+/// Howard Hinnant, years of the algorithm start on March 1st
+fn month_start(days: i64) -> i64 {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month + 2) / 5;
+    days - day_of_month
+}
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
 impl From<KlineInterval> for Duration {
     fn from(value: KlineInterval) -> Self {
         match value {
-            KlineInterval::I1m => Duration::minutes(1),
-            KlineInterval::I3m => Duration::minutes(3),
-            KlineInterval::I5m => Duration::minutes(5),
-            KlineInterval::I15m => Duration::minutes(15),
-            KlineInterval::I30m => Duration::minutes(30),
-            KlineInterval::I1h => Duration::hours(1),
-            KlineInterval::I2h => Duration::hours(2),
-            KlineInterval::I4h => Duration::hours(4),
-            KlineInterval::I6h => Duration::hours(6),
-            KlineInterval::I8h => Duration::hours(8),
-            KlineInterval::I12h => Duration::hours(12),
-            KlineInterval::I1d => Duration::days(1),
-            KlineInterval::I3d => Duration::days(3),
-            KlineInterval::I1w => Duration::weeks(1),
-            KlineInterval::I1M => Duration::days(30),
+            KlineInterval::I1m => Duration::from_secs(MINUTE),
+            KlineInterval::I3m => Duration::from_secs(3 * MINUTE),
+            KlineInterval::I5m => Duration::from_secs(5 * MINUTE),
+            KlineInterval::I15m => Duration::from_secs(15 * MINUTE),
+            KlineInterval::I30m => Duration::from_secs(30 * MINUTE),
+            KlineInterval::I1h => Duration::from_secs(HOUR),
+            KlineInterval::I2h => Duration::from_secs(2 * HOUR),
+            KlineInterval::I4h => Duration::from_secs(4 * HOUR),
+            KlineInterval::I6h => Duration::from_secs(6 * HOUR),
+            KlineInterval::I8h => Duration::from_secs(8 * HOUR),
+            KlineInterval::I12h => Duration::from_secs(12 * HOUR),
+            KlineInterval::I1d => Duration::from_secs(DAY),
+            KlineInterval::I3d => Duration::from_secs(3 * DAY),
+            KlineInterval::I1w => Duration::from_secs(7 * DAY),
+            KlineInterval::I1M => Duration::from_secs(30 * DAY),
         }
     }
 }
@@ -227,6 +318,7 @@ pub enum SelfTradePreventionMode {
     ExpireBoth,
     ExpireMaker,
 }
+impl_enum_str!(SelfTradePreventionMode);
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -234,8 +326,9 @@ pub enum MarginType {
     Isolated,
     Crossed,
 }
+impl_enum_str!(MarginType);
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PriceMatch {
     None,
@@ -248,6 +341,7 @@ pub enum PriceMatch {
     Queue10,
     Queue20,
 }
+impl_enum_str!(PriceMatch);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "filterType", rename_all = "SCREAMING_SNAKE_CASE")]
@@ -260,10 +354,73 @@ pub enum SymbolFilter {
     PercentPrice(PercentPrice),
     MinNotional(MinNotional),
     PositionRiskControl(PositionRiskControl),
+    /// Filter types which are not known yet, kept as raw json
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+/// Accessors of the filters of a symbol, e.g. `Filters(&symbol_info.filters).min_qty()`
+#[derive(Debug, Clone, Copy)]
+pub struct Filters<'a>(pub &'a [SymbolFilter]);
+
+impl<'a> Filters<'a> {
+    pub fn price_filter(&self) -> Option<&'a PriceFilter> {
+        self.0.iter().find_map(|i| match i {
+            SymbolFilter::PriceFilter(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    pub fn lot_size(&self) -> Option<&'a LotSize> {
+        self.0.iter().find_map(|i| match i {
+            SymbolFilter::LotSize(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    /// Lot size of market orders
+    pub fn market_lot_size(&self) -> Option<&'a LotSize> {
+        self.0.iter().find_map(|i| match i {
+            SymbolFilter::MarketLotSize(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    pub fn min_qty(&self) -> Option<Decimal> {
+        self.lot_size().map(|i| i.min_qty)
+    }
+
+    pub fn max_qty(&self) -> Option<Decimal> {
+        self.lot_size().map(|i| i.max_qty)
+    }
+
+    pub fn step_size(&self) -> Option<Decimal> {
+        self.lot_size().map(|i| i.step_size)
+    }
+
+    pub fn tick_size(&self) -> Option<Decimal> {
+        self.price_filter().map(|i| i.tick_size)
+    }
+
+    pub fn min_notional(&self) -> Option<Decimal> {
+        self.0.iter().find_map(|i| match i {
+            SymbolFilter::MinNotional(v) => Some(v.notional),
+            _ => None,
+        })
+    }
+
+    /// Max number of open orders
+    pub fn max_num_orders(&self) -> Option<i64> {
+        self.0.iter().find_map(|i| match i {
+            SymbolFilter::MaxNumOrders(v) => Some(v.limit),
+            _ => None,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct PriceFilter {
     pub max_price: Decimal,
     pub min_price: Decimal,
@@ -272,6 +429,7 @@ pub struct PriceFilter {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct LotSize {
     pub max_qty: Decimal,
     pub min_qty: Decimal,
@@ -280,12 +438,14 @@ pub struct LotSize {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct MaxNumOrders {
     pub limit: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct PercentPrice {
     pub multiplier_up: Decimal,
     pub multiplier_down: Decimal,
@@ -294,12 +454,14 @@ pub struct PercentPrice {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct MinNotional {
     pub notional: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct PositionRiskControl {
     pub position_control_side: String,
 }
@@ -309,10 +471,14 @@ pub struct PositionRiskControl {
 pub enum RateLimit {
     RequestWeight(RateLimitDetail),
     Orders(RateLimitDetail),
+    /// Rate limit types which are not known yet, kept as raw json
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct RateLimitDetail {
     pub interval: RateLimitInterval,
     pub interval_num: u64,
@@ -325,3 +491,76 @@ pub enum RateLimitInterval {
     Minute,
     Second,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IncomeType {
+    Transfer,
+    WelcomeBonus,
+    RealizedPnl,
+    FundingFee,
+    Commission,
+    InsuranceClear,
+    ReferralKickback,
+    CommissionRebate,
+    ApiRebate,
+    ContestReward,
+    CrossCollateralTransfer,
+    OptionsPremiumFee,
+    OptionsSettleProfit,
+    InternalTransfer,
+    AutoExchange,
+    #[serde(rename = "DELIVERED_SETTELMENT")]
+    DeliveredSettlement,
+    CoinSwapDeposit,
+    CoinSwapWithdraw,
+    PositionLimitIncreaseFee,
+    StrategyUmfuturesTransfer,
+    FeeReturn,
+    BfusdReward,
+    /// Types which are not known yet
+    #[serde(other)]
+    Other,
+}
+impl_enum_str!(IncomeType, IncomeType::Other);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadStatus {
+    Processing,
+    Completed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kline_start_time() {
+        // 2024-03-13T12:00:00Z, a Wednesday
+        let time = 1710331200000;
+        assert_eq!(KlineInterval::I1h.get_start_time(time), time);
+        assert_eq!(KlineInterval::I4h.get_start_time(time + 1), time);
+        // 2024-03-13T00:00:00Z
+        assert_eq!(KlineInterval::I1d.get_start_time(time), 1710288000000);
+        // Monday 2024-03-11T00:00:00Z
+        assert_eq!(KlineInterval::I1w.get_start_time(time), 1710115200000);
+        assert_eq!(
+            KlineInterval::I1w.get_start_time(1710115200000),
+            1710115200000
+        );
+        assert_eq!(
+            KlineInterval::I1w.get_start_time(1710115200000 - 1),
+            1710115200000 - 7 * 86400000
+        );
+        // 2024-03-01T00:00:00Z
+        assert_eq!(KlineInterval::I1M.get_start_time(time), 1709251200000);
+        // 2024-02-29T23:59:59.999Z is in February 2024-02-01T00:00:00Z
+        assert_eq!(
+            KlineInterval::I1M.get_start_time(1709251200000 - 1),
+            1706745600000
+        );
+        // 1969-12-31T00:00:00Z is in December 1969-12-01T00:00:00Z
+        assert_eq!(KlineInterval::I1M.get_start_time(-86400000), -31 * 86400000);
+    }
+}
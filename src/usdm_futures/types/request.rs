@@ -2,19 +2,28 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    KlineInterval, MarginType, NewOrderRespType, OrderSide, OrderType, PositionSide, PriceMatch,
-    SelfTradePreventionMode, TimeInForce, WorkingType,
+    ContractType, IncomeType, KlineInterval, MarginType, NewOrderRespType, OrderSide, OrderType,
+    PositionSide, PriceMatch, SelfTradePreventionMode, Symbol, TimeInForce, WorkingType,
 };
+use crate::{
+    error::{Error, Result},
+    timestamp,
+};
+
+/// Min delay in milliseconds of `good_till_date` from now
+const GOOD_TILL_DATE_MIN_DELAY: i64 = 600 * 1000;
+/// Upper bound(exclusive) of `good_till_date`
+const GOOD_TILL_DATE_MAX: i64 = 253402300799000;
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct OptionalSymbol {
-    pub symbol: Option<String>,
+    pub symbol: Option<Symbol>,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderId {
-    symbol: String,
+    symbol: Symbol,
     #[serde(flatten)]
     order_id: OrderIdInner,
 }
@@ -22,14 +31,14 @@ pub struct OrderId {
 impl OrderId {
     pub fn new_bn(symbol: &str, bn_id: i64) -> Self {
         Self {
-            symbol: symbol.to_string(),
+            symbol: symbol.into(),
             order_id: OrderIdInner::OrderId(bn_id),
         }
     }
 
     pub fn new_client(symbol: &str, client_id: &str) -> Self {
         Self {
-            symbol: symbol.to_string(),
+            symbol: symbol.into(),
             order_id: OrderIdInner::OrigClientOrderId(client_id.to_string()),
         }
     }
@@ -45,7 +54,7 @@ enum OrderIdInner {
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AlgoOrderId {
-    symbol: String,
+    symbol: Symbol,
     #[serde(flatten)]
     order_id: AlgoOrderIdInner,
 }
@@ -73,7 +82,7 @@ pub enum AlgoOrderIdInner {
 impl From<(&str, i64)> for AlgoOrderId {
     fn from((symbol, id): (&str, i64)) -> Self {
         AlgoOrderId {
-            symbol: symbol.to_string(),
+            symbol: symbol.into(),
             order_id: AlgoOrderIdInner::AlgoId(id),
         }
     }
@@ -82,7 +91,7 @@ impl From<(&str, i64)> for AlgoOrderId {
 impl From<(&str, &str)> for AlgoOrderId {
     fn from((symbol, id): (&str, &str)) -> Self {
         AlgoOrderId {
-            symbol: symbol.to_string(),
+            symbol: symbol.into(),
             order_id: AlgoOrderIdInner::ClientAlgoId(id.to_string()),
         }
     }
@@ -91,7 +100,7 @@ impl From<(&str, &str)> for AlgoOrderId {
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AllOrders {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub order_id: Option<u64>,
     pub start_time: Option<i64>,
     pub end_time: Option<i64>,
@@ -101,24 +110,29 @@ pub struct AllOrders {
 #[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NewOrder {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub side: OrderSide,
     #[serde(rename = "type")]
     pub order_type: OrderType,
     pub position_side: Option<PositionSide>,
     pub time_in_force: Option<TimeInForce>,
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub quantity: Option<Decimal>,
     pub reduce_only: Option<bool>,
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub price: Option<Decimal>,
     /// A unique id among open orders. Automatically generated if not sent. Can only be string following the rule: `^[\.A-Z\:/a-z0-9_-]{1,36}$`
     pub new_client_order_id: Option<String>,
     /// Used with `STOP/STOP_MARKET` or `TAKE_PROFIT/TAKE_PROFIT_MARKET` orders.
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub stop_price: Option<Decimal>,
     /// Close-All，used with `STOP_MARKET` or `TAKE_PROFIT_MARKET`.
     pub close_position: Option<bool>,
     /// Used with `TRAILING_STOP_MARKET` orders, default as the latest price(supporting different `workingType`)
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub activation_price: Option<Decimal>,
     /// Used with `TRAILING_STOP_MARKET` orders, min 0.1, max 10 where 1 for 1%
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub callback_rate: Option<Decimal>,
     pub working_type: Option<WorkingType>,
     /// "TRUE" or "FALSE", default "FALSE". Used with `STOP/STOP_MARKET` or `TAKE_PROFIT/TAKE_PROFIT_MARKET` orders.
@@ -130,20 +144,104 @@ pub struct NewOrder {
     pub good_till_date: Option<i64>,
 }
 
+impl NewOrder {
+    /// Limit order with `GTC` time in force
+    pub fn limit(symbol: &str, side: OrderSide, quantity: Decimal, price: Decimal) -> Self {
+        Self {
+            symbol: symbol.into(),
+            side,
+            order_type: OrderType::Limit,
+            time_in_force: Some(TimeInForce::Gtc),
+            quantity: Some(quantity),
+            price: Some(price),
+            ..Default::default()
+        }
+    }
+
+    pub fn market(symbol: &str, side: OrderSide, quantity: Decimal) -> Self {
+        Self {
+            symbol: symbol.into(),
+            side,
+            order_type: OrderType::Market,
+            quantity: Some(quantity),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_client_order_id(mut self, client_order_id: &str) -> Self {
+        self.new_client_order_id = Some(client_order_id.to_string());
+        self
+    }
+
+    pub fn with_position_side(mut self, position_side: PositionSide) -> Self {
+        self.position_side = Some(position_side);
+        self
+    }
+
+    pub fn with_reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = Some(reduce_only);
+        self
+    }
+
+    /// Cancel the order at `good_till_date` in milliseconds with `GTD` time in force
+    pub fn with_good_till_date(mut self, good_till_date: i64) -> Self {
+        self.time_in_force = Some(TimeInForce::Gtd);
+        self.good_till_date = Some(good_till_date);
+        self
+    }
+
+    /// Check the parameter combinations which are rejected by binance with obscure messages
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .price_match
+            .as_ref()
+            .is_some_and(|i| !matches!(i, PriceMatch::None))
+        {
+            if self.price.is_some() {
+                return Err(Error::new("price can not be sent with price match"));
+            }
+            if !matches!(
+                self.order_type,
+                OrderType::Limit | OrderType::Stop | OrderType::TakeProfit
+            ) {
+                return Err(Error::new(&format!(
+                    "price match is only used with LIMIT/STOP/TAKE_PROFIT order, not {}",
+                    self.order_type
+                )));
+            }
+        }
+        if matches!(self.time_in_force, Some(TimeInForce::Gtd)) {
+            let Some(good_till_date) = self.good_till_date else {
+                return Err(Error::new("good till date is required for GTD order"));
+            };
+            let min = timestamp::now_millis() + GOOD_TILL_DATE_MIN_DELAY;
+            if good_till_date < min || good_till_date >= GOOD_TILL_DATE_MAX {
+                return Err(Error::new(&format!(
+                    "good till date {good_till_date} must be at least 600 seconds later and before {GOOD_TILL_DATE_MAX}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NewAlgoOrder {
     /// Only support CONDITIONAL
     pub algo_type: String,
-    pub symbol: String,
+    pub symbol: Symbol,
     pub side: OrderSide,
     pub position_side: Option<PositionSide>,
     /// For CONDITIONAL algoType, STOP_MARKET/TAKE_PROFIT_MARKET/STOP/TAKE_PROFIT/TRAILING_STOP_MARKET as order type
     #[serde(rename = "type")]
     pub order_type: OrderType,
     pub time_in_force: Option<TimeInForce>,
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub quantity: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub price: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub trigger_price: Option<Decimal>,
     pub working_type: Option<WorkingType>,
     pub price_match: Option<PriceMatch>,
@@ -152,8 +250,10 @@ pub struct NewAlgoOrder {
     pub price_protect: Option<bool>,
     pub reduce_only: Option<bool>,
     /// Used with `TRAILING_STOP_MARKET` orders, default as the latest price(supporting different `workingType`)
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub activation_price: Option<Decimal>,
     /// Used with `TRAILING_STOP_MARKET` orders, min 0.1, max 10 where 1 for 1%
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub callback_rate: Option<Decimal>,
     /// A unique id among open orders. Automatically generated if not sent. Can only be string following the rule: ^[\.A-Z\:/a-z0-9_-]{1,36}$
     pub client_algo_id: Option<String>,
@@ -163,10 +263,46 @@ pub struct NewAlgoOrder {
     pub good_till_date: Option<i64>,
 }
 
+impl NewAlgoOrder {
+    /// Conditional `STOP_MARKET` order triggered at `trigger_price`, stop market orders are
+    /// placed as algo orders by binance.
+    pub fn stop_market(
+        symbol: &str,
+        side: OrderSide,
+        quantity: Decimal,
+        trigger_price: Decimal,
+    ) -> Self {
+        Self {
+            algo_type: "CONDITIONAL".to_string(),
+            symbol: symbol.into(),
+            side,
+            order_type: OrderType::StopMarket,
+            quantity: Some(quantity),
+            trigger_price: Some(trigger_price),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_client_algo_id(mut self, client_algo_id: &str) -> Self {
+        self.client_algo_id = Some(client_algo_id.to_string());
+        self
+    }
+
+    pub fn with_position_side(mut self, position_side: PositionSide) -> Self {
+        self.position_side = Some(position_side);
+        self
+    }
+
+    pub fn with_reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = Some(reduce_only);
+        self
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountTradeList {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub order_id: Option<u64>,
     pub start_time: Option<i64>,
     pub end_time: Option<i64>,
@@ -177,14 +313,14 @@ pub struct AccountTradeList {
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChangeMarginType {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub margin_type: MarginType,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChangeInitialLeverage {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub leverage: u8,
 }
 
@@ -203,7 +339,8 @@ pub struct ChangePositionMode {
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModifyIsolatedPositionMargin {
-    pub symbol: String,
+    pub symbol: Symbol,
+    #[serde(with = "rust_decimal::serde::str")]
     pub amount: Decimal,
     // 1: Add position margin，2: Reduce position margin
     #[serde(rename = "type")]
@@ -217,15 +354,40 @@ pub struct ModifyOrder {
     #[serde(flatten)]
     pub order_id: OrderId,
     pub side: OrderSide,
-    pub quantity: Decimal,
-    pub price: Decimal,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub quantity: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub price: Option<Decimal>,
     pub price_match: Option<PriceMatch>,
 }
 
+impl ModifyOrder {
+    /// Quantity is required, and either price or price match, but not both
+    pub fn validate(&self) -> Result<()> {
+        if self.quantity.is_none() {
+            return Err(Error::new("quantity is required to modify an order"));
+        }
+        if self.price.is_none() && self.price_match.is_none() {
+            return Err(Error::new(
+                "price or price match is required to modify an order",
+            ));
+        }
+        if self.price.is_some()
+            && self
+                .price_match
+                .as_ref()
+                .is_some_and(|i| !matches!(i, PriceMatch::None))
+        {
+            return Err(Error::new("price can not be sent with price match"));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct KlineCandlestickData {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub interval: KlineInterval,
     pub start_time: Option<i64>,
     pub end_time: Option<i64>,
@@ -235,17 +397,366 @@ pub struct KlineCandlestickData {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OpenInterestHist {
-    pub symbol: String,
+    pub symbol: Symbol,
+    pub period: KlineInterval,
+    pub limit: Option<i64>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Basis {
+    pub pair: String,
+    /// `CURRENT_QUARTER`, `NEXT_QUARTER` or `PERPETUAL`
+    pub contract_type: ContractType,
     pub period: KlineInterval,
+    /// Default 30, max 500
     pub limit: Option<i64>,
     pub start_time: Option<i64>,
     pub end_time: Option<i64>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryPrice {
+    pub pair: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBook {
+    pub symbol: Symbol,
+    /// 5, 10, 20, 50, 100, 500(default) or 1000
+    pub limit: Option<u16>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoricalTrades {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub limit: Option<i64>,
     pub from_id: Option<i64>,
 }
+
+/// Income history in `[start_time, end_time]`, in milliseconds and at most 3 months, the last 7
+/// days by default
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomeHistory {
+    pub symbol: Option<Symbol>,
+    pub income_type: Option<IncomeType>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub page: Option<u32>,
+    /// Default 100, max 1000
+    pub limit: Option<u32>,
+}
+
+/// Time range of the exported history, in milliseconds and at most 1 year
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadIdTransactionHistory {
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadId {
+    pub download_id: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query<T: Serialize>(req: &T) -> String {
+        serde_urlencoded::to_string(req).unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn optional_symbol() {
+        assert_eq!(query(&OptionalSymbol { symbol: None }), "");
+        let req = OptionalSymbol {
+            symbol: Some("btcusdt".into()),
+        };
+        assert_eq!(query(&req), "symbol=BTCUSDT");
+    }
+
+    #[test]
+    fn order_id() {
+        assert_eq!(
+            query(&OrderId::new_bn("btcusdt", 42)),
+            "symbol=BTCUSDT&orderId=42"
+        );
+        assert_eq!(
+            query(&OrderId::new_client("BTCUSDT", "abc")),
+            "symbol=BTCUSDT&origClientOrderId=abc"
+        );
+    }
+
+    #[test]
+    fn algo_order_id() {
+        let req = AlgoOrderId::from(OrderId::new_bn("BTCUSDT", 42));
+        assert_eq!(query(&req), "symbol=BTCUSDT&algoId=42");
+        let req = AlgoOrderId::from(OrderId::new_client("BTCUSDT", "abc"));
+        assert_eq!(query(&req), "symbol=BTCUSDT&clientAlgoId=abc");
+        assert_eq!(
+            query(&AlgoOrderId::from(("BTCUSDT", 7))),
+            "symbol=BTCUSDT&algoId=7"
+        );
+        assert_eq!(
+            query(&AlgoOrderId::from(("BTCUSDT", "abc"))),
+            "symbol=BTCUSDT&clientAlgoId=abc"
+        );
+    }
+
+    #[test]
+    fn all_orders() {
+        let req = AllOrders {
+            symbol: "BTCUSDT".into(),
+            order_id: None,
+            start_time: None,
+            end_time: None,
+            limit: None,
+        };
+        assert_eq!(query(&req), "symbol=BTCUSDT");
+        let req = AllOrders {
+            symbol: "BTCUSDT".into(),
+            order_id: Some(42),
+            start_time: Some(1000),
+            end_time: None,
+            limit: Some(10),
+        };
+        assert_eq!(
+            query(&req),
+            "symbol=BTCUSDT&orderId=42&startTime=1000&limit=10"
+        );
+    }
+
+    #[test]
+    fn new_order() {
+        let req = NewOrder::limit("btcusdt", OrderSide::Buy, dec("0.001"), dec("100.5"));
+        assert_eq!(
+            query(&req),
+            "symbol=BTCUSDT&side=BUY&type=LIMIT&timeInForce=GTC&quantity=0.001&price=100.5"
+        );
+        let req = NewOrder::market("BTCUSDT", OrderSide::Sell, dec("0.001"))
+            .with_reduce_only(true)
+            .with_position_side(PositionSide::Long)
+            .with_client_order_id("x1");
+        assert_eq!(
+            query(&req),
+            "symbol=BTCUSDT&side=SELL&type=MARKET&positionSide=LONG&quantity=0.001&reduceOnly=true&newClientOrderId=x1"
+        );
+    }
+
+    #[test]
+    fn new_order_constructors() {
+        let req = NewOrder::limit("btcusdt", OrderSide::Sell, dec("0.001"), dec("100.5"));
+        assert_eq!(req.symbol.as_str(), "BTCUSDT");
+        assert!(matches!(req.side, OrderSide::Sell));
+        assert_eq!(req.order_type, OrderType::Limit);
+        assert!(matches!(req.time_in_force, Some(TimeInForce::Gtc)));
+        assert_eq!(req.quantity, Some(dec("0.001")));
+        assert_eq!(req.price, Some(dec("100.5")));
+        assert!(req.validate().is_ok());
+
+        let req = NewOrder::market("BTCUSDT", OrderSide::Buy, dec("2"));
+        assert_eq!(req.order_type, OrderType::Market);
+        assert!(req.time_in_force.is_none());
+        assert_eq!(req.quantity, Some(dec("2")));
+        assert!(req.price.is_none());
+        assert!(req.validate().is_ok());
+
+        let req = NewOrder::limit("BTCUSDT", OrderSide::Buy, dec("1"), dec("100"))
+            .with_good_till_date(timestamp::now_millis());
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn new_algo_order() {
+        let req = NewAlgoOrder::stop_market("BTCUSDT", OrderSide::Sell, dec("0.001"), dec("90"))
+            .with_client_algo_id("x1");
+        assert_eq!(
+            query(&req),
+            "algoType=CONDITIONAL&symbol=BTCUSDT&side=SELL&type=STOP_MARKET&quantity=0.001&triggerPrice=90&clientAlgoId=x1"
+        );
+    }
+
+    #[test]
+    fn account_trade_list() {
+        let req = AccountTradeList {
+            symbol: "BTCUSDT".into(),
+            order_id: None,
+            start_time: None,
+            end_time: Some(2000),
+            from_id: Some(7),
+            limit: None,
+        };
+        assert_eq!(query(&req), "symbol=BTCUSDT&endTime=2000&fromId=7");
+    }
+
+    #[test]
+    fn account_settings() {
+        let req = ChangeMarginType {
+            symbol: "BTCUSDT".into(),
+            margin_type: MarginType::Isolated,
+        };
+        assert_eq!(query(&req), "symbol=BTCUSDT&marginType=ISOLATED");
+        let req = ChangeInitialLeverage {
+            symbol: "BTCUSDT".into(),
+            leverage: 20,
+        };
+        assert_eq!(query(&req), "symbol=BTCUSDT&leverage=20");
+        let req = ChangeMultiAssetsMode {
+            multi_assets_margin: true,
+        };
+        assert_eq!(query(&req), "multiAssetsMargin=true");
+        let req = ChangePositionMode {
+            dual_side_position: false,
+        };
+        assert_eq!(query(&req), "dualSidePosition=false");
+    }
+
+    #[test]
+    fn modify_isolated_position_margin() {
+        let req = ModifyIsolatedPositionMargin {
+            symbol: "BTCUSDT".into(),
+            amount: dec("10.5"),
+            _type: 1,
+            position_side: Some(PositionSide::Long),
+        };
+        assert_eq!(
+            query(&req),
+            "symbol=BTCUSDT&amount=10.5&type=1&positionSide=LONG"
+        );
+    }
+
+    #[test]
+    fn modify_order() {
+        let req = ModifyOrder {
+            order_id: OrderId::new_bn("BTCUSDT", 42),
+            side: OrderSide::Buy,
+            quantity: Some(dec("0.002")),
+            price: Some(dec("101")),
+            price_match: None,
+        };
+        assert_eq!(
+            query(&req),
+            "symbol=BTCUSDT&orderId=42&side=BUY&quantity=0.002&price=101"
+        );
+        let req = ModifyOrder {
+            order_id: OrderId::new_client("BTCUSDT", "abc"),
+            side: OrderSide::Sell,
+            quantity: Some(dec("0.002")),
+            price: None,
+            price_match: Some(PriceMatch::Queue),
+        };
+        assert_eq!(
+            query(&req),
+            "symbol=BTCUSDT&origClientOrderId=abc&side=SELL&quantity=0.002&priceMatch=QUEUE"
+        );
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_modify_order() {
+        let modify = |quantity: Option<&str>, price: Option<&str>, price_match| ModifyOrder {
+            order_id: OrderId::new_bn("BTCUSDT", 42),
+            side: OrderSide::Buy,
+            quantity: quantity.map(dec),
+            price: price.map(dec),
+            price_match,
+        };
+        assert!(modify(Some("1"), Some("100"), None).validate().is_ok());
+        assert!(
+            modify(Some("1"), None, Some(PriceMatch::Opponent))
+                .validate()
+                .is_ok()
+        );
+        assert!(modify(None, Some("100"), None).validate().is_err());
+        assert!(modify(Some("1"), None, None).validate().is_err());
+        assert!(
+            modify(Some("1"), Some("100"), Some(PriceMatch::Queue))
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn market_data() {
+        let req = KlineCandlestickData {
+            symbol: "BTCUSDT".into(),
+            interval: KlineInterval::I1h,
+            start_time: Some(1000),
+            end_time: None,
+            limit: Some(500),
+        };
+        assert_eq!(
+            query(&req),
+            "symbol=BTCUSDT&interval=1h&startTime=1000&limit=500"
+        );
+        let req = OpenInterestHist {
+            symbol: "BTCUSDT".into(),
+            period: KlineInterval::I5m,
+            limit: Some(30),
+            start_time: None,
+            end_time: None,
+        };
+        assert_eq!(query(&req), "symbol=BTCUSDT&period=5m&limit=30");
+        let req = Basis {
+            pair: "BTCUSDT".to_string(),
+            contract_type: ContractType::Perpetual,
+            period: KlineInterval::I1d,
+            limit: None,
+            start_time: None,
+            end_time: None,
+        };
+        assert_eq!(query(&req), "pair=BTCUSDT&contractType=PERPETUAL&period=1d");
+        let req = DeliveryPrice {
+            pair: "BTCUSDT".to_string(),
+        };
+        assert_eq!(query(&req), "pair=BTCUSDT");
+        let req = OrderBook {
+            symbol: "BTCUSDT".into(),
+            limit: Some(5),
+        };
+        assert_eq!(query(&req), "symbol=BTCUSDT&limit=5");
+        let req = HistoricalTrades {
+            symbol: "BTCUSDT".into(),
+            limit: None,
+            from_id: Some(7),
+        };
+        assert_eq!(query(&req), "symbol=BTCUSDT&fromId=7");
+    }
+
+    #[test]
+    fn income_history() {
+        assert_eq!(query(&IncomeHistory::default()), "");
+        let req = IncomeHistory {
+            income_type: Some(IncomeType::RealizedPnl),
+            page: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(query(&req), "incomeType=REALIZED_PNL&page=2");
+    }
+
+    #[test]
+    fn download() {
+        let req = DownloadIdTransactionHistory {
+            start_time: 1000,
+            end_time: 2000,
+        };
+        assert_eq!(query(&req), "startTime=1000&endTime=2000");
+        let req = DownloadId {
+            download_id: "abc".to_string(),
+        };
+        assert_eq!(query(&req), "downloadId=abc");
+    }
+}
@@ -1,11 +1,21 @@
-use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 
 use super::{
-    ContractStatus, ContractType, MarginType, OrderSide, OrderStatus, OrderType, PositionSide,
-    PriceMatch, RateLimit, SelfTradePreventionMode, SymbolFilter, TimeInForce, WorkingType,
+    ContractStatus, ContractType, DownloadStatus, Filters, IncomeType, MarginType, OrderSide,
+    OrderStatus, OrderType, PositionSide, PriceMatch, RateLimit, SelfTradePreventionMode,
+    SymbolFilter, TimeInForce, WorkingType,
 };
-use crate::error::Error;
+use crate::{error::Error, util};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct ServerTime {
+    pub server_time: i64,
+}
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
@@ -16,6 +26,7 @@ pub enum Ticker24hr {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Ticker24hrItem {
     pub symbol: String,
     pub price_change: Decimal,
@@ -53,6 +64,7 @@ impl From<TickerPrice> for Vec<TickerPriceItem> {
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct TickerPriceItem {
     pub symbol: String,
     pub price: Decimal,
@@ -61,6 +73,7 @@ pub struct TickerPriceItem {
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct KlineCandlestickData {
     pub open: Decimal,
     pub high: Decimal,
@@ -68,7 +81,7 @@ pub struct KlineCandlestickData {
     pub close: Decimal,
     pub volume: Decimal,
     pub quote_asset_volume: Decimal,
-    pub number_of_trades: i64,
+    pub number_of_trades: u64,
     pub taker_buy_base_asset_volume: Decimal,
     pub taker_buy_quote_asset_volume: Decimal,
     pub open_time: i64,
@@ -94,6 +107,12 @@ impl TryFrom<serde_json::Value> for KlineCandlestickData {
                 .ok_or_else(|| Error::Serde("expect a json number".to_string()))
         }
 
+        fn parse_u64(v: &serde_json::Value) -> Result<u64, Error> {
+            v.as_number()
+                .and_then(|i| i.as_u64())
+                .ok_or_else(|| Error::Serde("expect a json unsigned number".to_string()))
+        }
+
         fn parse_decimal(v: &serde_json::Value) -> Result<Decimal, Error> {
             let s = v
                 .as_str()
@@ -110,17 +129,102 @@ impl TryFrom<serde_json::Value> for KlineCandlestickData {
             volume: parse_decimal(&v[5])?,
             close_time: parse_i64(&v[6])?,
             quote_asset_volume: parse_decimal(&v[7])?,
-            number_of_trades: parse_i64(&v[8])?,
+            number_of_trades: parse_u64(&v[8])?,
             taker_buy_base_asset_volume: parse_decimal(&v[9])?,
             taker_buy_quote_asset_volume: parse_decimal(&v[10])?,
         })
     }
 }
 
+impl KlineCandlestickData {
+    /// Convert rows of kline data into columns
+    pub fn into_columns(v: Vec<KlineCandlestickData>) -> Klines {
+        v.into()
+    }
+}
+
+/// Column-major kline data, every column has the same length
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct Klines {
+    pub open_time: Vec<i64>,
+    pub open: Vec<Decimal>,
+    pub high: Vec<Decimal>,
+    pub low: Vec<Decimal>,
+    pub close: Vec<Decimal>,
+    pub volume: Vec<Decimal>,
+    pub close_time: Vec<i64>,
+    pub quote_asset_volume: Vec<Decimal>,
+    pub number_of_trades: Vec<u64>,
+    pub taker_buy_base_asset_volume: Vec<Decimal>,
+    pub taker_buy_quote_asset_volume: Vec<Decimal>,
+}
+
+impl Klines {
+    pub fn len(&self) -> usize {
+        self.open_time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.open_time.is_empty()
+    }
+
+    /// Convert columns back into rows of kline data
+    pub fn into_rows(self) -> Vec<KlineCandlestickData> {
+        self.into()
+    }
+}
+
+impl From<Vec<KlineCandlestickData>> for Klines {
+    fn from(value: Vec<KlineCandlestickData>) -> Self {
+        let mut klines = Klines::default();
+        for i in value {
+            klines.open_time.push(i.open_time);
+            klines.open.push(i.open);
+            klines.high.push(i.high);
+            klines.low.push(i.low);
+            klines.close.push(i.close);
+            klines.volume.push(i.volume);
+            klines.close_time.push(i.close_time);
+            klines.quote_asset_volume.push(i.quote_asset_volume);
+            klines.number_of_trades.push(i.number_of_trades);
+            klines
+                .taker_buy_base_asset_volume
+                .push(i.taker_buy_base_asset_volume);
+            klines
+                .taker_buy_quote_asset_volume
+                .push(i.taker_buy_quote_asset_volume);
+        }
+        klines
+    }
+}
+
+impl From<Klines> for Vec<KlineCandlestickData> {
+    fn from(value: Klines) -> Self {
+        (0..value.len())
+            .map(|i| KlineCandlestickData {
+                open_time: value.open_time[i],
+                open: value.open[i],
+                high: value.high[i],
+                low: value.low[i],
+                close: value.close[i],
+                volume: value.volume[i],
+                close_time: value.close_time[i],
+                quote_asset_volume: value.quote_asset_volume[i],
+                number_of_trades: value.number_of_trades[i],
+                taker_buy_base_asset_volume: value.taker_buy_base_asset_volume[i],
+                taker_buy_quote_asset_volume: value.taker_buy_quote_asset_volume[i],
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ExchangeInfo {
-    pub exchange_filters: Vec<String>,
+    pub exchange_filters: Vec<serde_json::Value>,
     pub rate_limits: Vec<RateLimit>,
     pub server_time: i64,
     pub assets: Vec<ExchangeInfoAsset>,
@@ -130,14 +234,16 @@ pub struct ExchangeInfo {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ExchangeInfoAsset {
     pub asset: String,
     pub margin_available: bool,
     pub auto_asset_exchange: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ExchangeInfoSymbol {
     pub symbol: String,
     pub pair: String,
@@ -165,19 +271,151 @@ pub struct ExchangeInfoSymbol {
     pub market_take_bound: Decimal,
 }
 
+impl ExchangeInfoSymbol {
+    /// Round price to the nearest multiple of the tick size
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        match Filters(&self.filters).tick_size() {
+            Some(tick_size) => {
+                util::round_to_step(price, tick_size, RoundingStrategy::MidpointNearestEven)
+            }
+            None => price,
+        }
+    }
+
+    /// Round quantity down to a multiple of the step size
+    pub fn round_qty(&self, qty: Decimal) -> Decimal {
+        match Filters(&self.filters).step_size() {
+            Some(step_size) => util::round_to_step(qty, step_size, RoundingStrategy::ToZero),
+            None => qty,
+        }
+    }
+
+    /// Round quantity of a market order down to a multiple of the step size of
+    /// `MARKET_LOT_SIZE`, the step size of `LOT_SIZE` is used if it's not set
+    pub fn round_market_qty(&self, qty: Decimal) -> Decimal {
+        let filters = Filters(&self.filters);
+        match filters.market_lot_size().or(filters.lot_size()) {
+            Some(lot_size) => {
+                util::round_to_step(qty, lot_size.step_size, RoundingStrategy::ToZero)
+            }
+            None => qty,
+        }
+    }
+
+    /// Check the notional of an order is not less than the min notional
+    pub fn validate_notional(&self, price: Decimal, qty: Decimal) -> Result<(), Error> {
+        if let Some(min_notional) = Filters(&self.filters).min_notional() {
+            let notional = price * qty;
+            if notional < min_notional {
+                return Err(Error::new(&format!(
+                    "notional {notional} of {} is less than min notional {min_notional}",
+                    self.symbol
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct OpenInterestHist {
     pub symbol: String,
     pub sum_open_interest: Decimal,
     pub sum_open_interest_value: Decimal,
     #[serde(rename = "CMCCirculatingSupply")]
     pub cmccirculating_supply: Decimal,
-    pub timestamp: Decimal,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct Basis {
+    pub index_price: Decimal,
+    pub contract_type: ContractType,
+    pub basis_rate: Decimal,
+    pub futures_price: Decimal,
+    /// Empty for perpetual contracts
+    pub annualized_basis_rate: String,
+    pub basis: Decimal,
+    pub pair: String,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct DeliveryPrice {
+    pub delivery_time: i64,
+    pub delivery_price: Decimal,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct ListenKey {
+    pub listen_key: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct OrderBook {
+    pub last_update_id: u64,
+    /// Message output time
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    /// Transaction time
+    #[serde(rename = "T")]
+    pub transaction_time: i64,
+    /// Price and quantity of bids
+    pub bids: Vec<(Decimal, Decimal)>,
+    /// Price and quantity of asks
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum IndexInfo {
+    One(Box<IndexInfoItem>),
+    Many(Vec<IndexInfoItem>),
+}
+
+impl From<IndexInfo> for Vec<IndexInfoItem> {
+    fn from(value: IndexInfo) -> Self {
+        match value {
+            IndexInfo::One(v) => vec![*v],
+            IndexInfo::Many(v) => v,
+        }
+    }
+}
+
+/// Composition of a composite index symbol
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct IndexInfoItem {
+    pub symbol: String,
+    pub time: i64,
+    pub component: String,
+    pub base_asset_list: Vec<IndexInfoComponent>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct IndexInfoComponent {
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub weight_in_quantity: Decimal,
+    pub weight_in_percentage: Decimal,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct HistoricalTrades {
     pub id: i64,
     pub price: Decimal,
@@ -187,8 +425,12 @@ pub struct HistoricalTrades {
     pub is_buyer_maker: bool,
 }
 
+/// Order info, a futures order response has no per-fill detail even with
+/// `newOrderRespType=RESULT`, only the aggregated `executed_qty`, `avg_price` and `cum_quote`.
+/// The fills of an order are queried by `userTrades` with the order id.
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct OrderInfo {
     pub avg_price: Decimal,
     pub client_order_id: String,
@@ -219,8 +461,21 @@ pub struct OrderInfo {
     pub good_till_date: i64,
 }
 
+/// Order acknowledge of `newOrderRespType=ACK`, only the ids of the order are guaranteed,
+/// the other fields may be missing, use `OrderInfo` by querying the order for the full state.
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+pub struct OrderAck {
+    pub order_id: u64,
+    pub client_order_id: String,
+    pub symbol: String,
+    pub status: Option<OrderStatus>,
+    pub update_time: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct AlgoOrderInfo {
     pub algo_id: u64,
     pub client_algo_id: String,
@@ -251,6 +506,7 @@ pub struct AlgoOrderInfo {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct CancelAlgoOrder {
     pub algo_id: u64,
     pub client_algo_id: String,
@@ -260,6 +516,7 @@ pub struct CancelAlgoOrder {
 
 #[derive(Debug, Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct AccountTradeList {
     pub buyer: bool,
     pub commission: Decimal,
@@ -277,6 +534,14 @@ pub struct AccountTradeList {
     pub time: i64,
 }
 
+/// Element of the response of batch orders, which is an order or an error of it
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BatchOrder {
+    Error { code: i64, msg: String },
+    Order(Box<OrderInfo>),
+}
+
 #[derive(Debug, Deserialize)]
 pub struct OperationResult {
     pub code: Option<i64>,
@@ -285,12 +550,23 @@ pub struct OperationResult {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ChangeInitialLeverage {
     pub leverage: Option<i64>,
     pub max_notional_value: Option<String>,
     pub symbol: Option<String>,
 }
 
+impl ChangeInitialLeverage {
+    /// Max notional value as a number, `None` if it's missing or `"INF"`
+    pub fn max_notional(&self) -> Option<Decimal> {
+        self.max_notional_value
+            .as_deref()
+            .filter(|v| !v.eq_ignore_ascii_case("INF"))
+            .and_then(|v| v.parse().ok())
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModifyIsolatedPositionMargin {
@@ -303,6 +579,7 @@ pub struct ModifyIsolatedPositionMargin {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct PositionInformationV3 {
     pub symbol: String,
     pub position_side: PositionSide,
@@ -326,8 +603,26 @@ pub struct PositionInformationV3 {
     pub update_time: i64,
 }
 
+impl PositionInformationV3 {
+    /// Unrealized profit at a mark price, e.g. of the mark price stream
+    pub fn unrealized_pnl_at(&self, mark_price: Decimal) -> Decimal {
+        util::unrealized_pnl(
+            self.entry_price,
+            mark_price,
+            self.position_amt,
+            self.position_side,
+        )
+    }
+
+    /// Distance in percent from a mark price to the liquidation price
+    pub fn liquidation_distance_pct(&self, mark_price: Decimal) -> Option<Decimal> {
+        util::liquidation_distance_pct(mark_price, self.liquidation_price)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct FuturesAccountBalanceV2 {
     pub account_alias: String,
     pub asset: String,
@@ -342,6 +637,7 @@ pub struct FuturesAccountBalanceV2 {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct AccountInformationV3 {
     pub total_initial_margin: Decimal,
     pub total_maint_margin: Decimal,
@@ -360,6 +656,7 @@ pub struct AccountInformationV3 {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct AccountInformationV3Asset {
     pub asset: String,
     pub wallet_balance: Decimal,
@@ -379,6 +676,7 @@ pub struct AccountInformationV3Asset {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct AccountInformationV3Position {
     pub symbol: String,
     pub position_side: PositionSide,
@@ -394,6 +692,7 @@ pub struct AccountInformationV3Position {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct SymbolConfiguration {
     pub symbol: String,
     pub margin_type: MarginType,
@@ -404,12 +703,21 @@ pub struct SymbolConfiguration {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct GetCurrentPositionMode {
     pub dual_side_position: bool,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct GetMultiAssetsMode {
+    pub multi_assets_margin: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct FuturesAccountConfiguration {
     pub fee_tier: u8,
     pub can_trade: bool,
@@ -420,3 +728,62 @@ pub struct FuturesAccountConfiguration {
     pub multi_assets_margin: bool,
     pub trade_group_id: i64,
 }
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct ApiTradingStatus {
+    /// Indicators keyed by symbol, or `ACCOUNT` for indicators of the whole account
+    pub indicators: HashMap<String, Vec<ApiTradingStatusIndicator>>,
+    pub update_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct ApiTradingStatusIndicator {
+    pub is_locked: bool,
+    pub planned_recover_time: i64,
+    /// `UFR`, `IFER`, `GCR`, `DR` or `TMV`
+    pub indicator: String,
+    pub value: Decimal,
+    pub trigger_value: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct DownloadIdTransactionHistory {
+    pub avg_cost_timestamp_of_last30d: i64,
+    pub download_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct IncomeHistory {
+    /// Empty if the income is not of a symbol, e.g. transfers
+    pub symbol: String,
+    pub income_type: IncomeType,
+    pub income: Decimal,
+    pub asset: String,
+    pub info: String,
+    pub time: i64,
+    pub tran_id: i64,
+    /// Empty if the income is not of a trade
+    pub trade_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct DownloadLink {
+    pub download_id: String,
+    pub status: DownloadStatus,
+    /// Empty while processing
+    pub url: String,
+    pub notified: bool,
+    /// `-1` while processing
+    pub expiration_timestamp: i64,
+    pub is_expired: Option<bool>,
+}
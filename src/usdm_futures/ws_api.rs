@@ -0,0 +1,226 @@
+//! Websocket API, requests and responses over a persistent connection with lower latency than
+//! the REST api
+
+use std::collections::BTreeMap;
+
+#[cfg(feature = "ed25519")]
+use base64::{Engine, prelude::BASE64_STANDARD};
+#[cfg(feature = "ed25519")]
+use ed25519_dalek::{Signer as _, SigningKey, pkcs8::DecodePrivateKey};
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use sha2::Sha256;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{
+    error::{Error, Result},
+    log::warn,
+    stream::{WsStream, close_error, connect},
+    timestamp,
+    usdm_futures::types::{request, response},
+};
+
+const URL: &str = "wss://ws-fapi.binance.com/ws-fapi/v1";
+
+#[derive(Serialize)]
+struct Request<'a> {
+    id: u64,
+    method: &'a str,
+    params: BTreeMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    id: Option<u64>,
+    status: u16,
+    result: Option<Value>,
+    error: Option<ErrorDetail>,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+    code: i64,
+    msg: String,
+}
+
+enum Secret {
+    Hmac(String),
+    #[cfg(feature = "ed25519")]
+    Ed25519(SigningKey),
+}
+
+/// Connection of the websocket API.
+///
+/// Requests are sent one at a time, every request waits for the response of its id, pings of
+/// server are answered meanwhile. Params are signed by the api secret with the local time, the
+/// clock should be kept in sync.
+pub struct WsApiClient {
+    stream: WsStream,
+    key: String,
+    secret: Secret,
+    next_id: u64,
+    /// Requests of an authenticated session carry no api key and signature
+    logged_on: bool,
+}
+
+impl WsApiClient {
+    /// Connect to the websocket API with a HMAC api key, the proxy is the same as streams
+    pub async fn connect(key: &str, secret: &str, proxy: Option<&str>) -> Result<Self> {
+        Self::connect_with(key, Secret::Hmac(secret.to_string()), proxy).await
+    }
+
+    /// Connect to the websocket API with an Ed25519 api key, the private key is in PKCS#8 PEM
+    #[cfg(feature = "ed25519")]
+    pub async fn connect_ed25519(
+        key: &str,
+        private_key_pem: &str,
+        proxy: Option<&str>,
+    ) -> Result<Self> {
+        let signing_key = SigningKey::from_pkcs8_pem(private_key_pem)
+            .map_err(|e| Error::new(&format!("invalid Ed25519 private key: {e}")))?;
+        Self::connect_with(key, Secret::Ed25519(signing_key), proxy).await
+    }
+
+    async fn connect_with(key: &str, secret: Secret, proxy: Option<&str>) -> Result<Self> {
+        let stream = connect(URL, proxy).await?;
+        Ok(WsApiClient {
+            stream,
+            key: key.to_string(),
+            secret,
+            next_id: 0,
+            logged_on: false,
+        })
+    }
+
+    /// Authenticate the connection by `session.logon`, following requests are not signed.
+    /// Only Ed25519 api keys are supported by binance.
+    #[cfg(feature = "ed25519")]
+    pub async fn session_logon(&mut self) -> Result<()> {
+        if !matches!(self.secret, Secret::Ed25519(_)) {
+            return Err(Error::new("session logon requires an Ed25519 api key"));
+        }
+        let params = self.signed_params(None::<()>)?;
+        self.request::<Value>("session.logon", params).await?;
+        self.logged_on = true;
+        Ok(())
+    }
+
+    /// Send a signed request of a method which is not wrapped yet, e.g. `account.status`, the
+    /// api key, timestamp and signature are added to the params, or only the timestamp after
+    /// `session_logon`.
+    pub async fn signed_request<P, R>(&mut self, method: &str, params: P) -> Result<R>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let params = if self.logged_on {
+            let mut params = into_params(params)?;
+            params.insert("timestamp".to_string(), timestamp::now_millis().into());
+            params
+        } else {
+            self.signed_params(params)?
+        };
+        self.request(method, params).await
+    }
+
+    /// Place an order by `order.place`
+    pub async fn place_order(&mut self, params: request::NewOrder) -> Result<response::OrderInfo> {
+        params.validate()?;
+        self.signed_request("order.place", params).await
+    }
+
+    /// Cancel an order by `order.cancel`
+    pub async fn cancel_order(&mut self, params: request::OrderId) -> Result<response::OrderInfo> {
+        self.signed_request("order.cancel", params).await
+    }
+
+    /// Check an order's status by `order.status`
+    pub async fn query_order(&mut self, params: request::OrderId) -> Result<response::OrderInfo> {
+        self.signed_request("order.status", params).await
+    }
+
+    /// Close the connection
+    pub async fn close(mut self) -> Result<()> {
+        Ok(self.stream.close(None).await?)
+    }
+
+    fn signed_params(&self, params: impl Serialize) -> Result<BTreeMap<String, Value>> {
+        let mut params = into_params(params)?;
+        params.insert("apiKey".to_string(), Value::String(self.key.clone()));
+        params.insert("timestamp".to_string(), timestamp::now_millis().into());
+        let signature = self.sign(&params)?;
+        params.insert("signature".to_string(), Value::String(signature));
+        Ok(params)
+    }
+
+    /// Signature of the params sorted by key like `key1=value1&key2=value2`, hex of HMAC or
+    /// base64 of Ed25519
+    fn sign(&self, params: &BTreeMap<String, Value>) -> Result<String> {
+        let payload = params
+            .iter()
+            .map(|(k, v)| match v {
+                Value::String(s) => format!("{k}={s}"),
+                v => format!("{k}={v}"),
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        match &self.secret {
+            Secret::Hmac(secret) => {
+                let mut mac: Hmac<Sha256> = Hmac::new_from_slice(secret.as_bytes())?;
+                mac.update(payload.as_bytes());
+                Ok(hex::encode(mac.finalize().into_bytes()))
+            }
+            #[cfg(feature = "ed25519")]
+            Secret::Ed25519(key) => {
+                Ok(BASE64_STANDARD.encode(key.sign(payload.as_bytes()).to_bytes()))
+            }
+        }
+    }
+
+    async fn request<R>(&mut self, method: &str, params: BTreeMap<String, Value>) -> Result<R>
+    where
+        R: DeserializeOwned,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = serde_json::to_string(&Request { id, method, params })?;
+        self.stream.send(Message::Text(request.into())).await?;
+        loop {
+            let Some(msg) = self.stream.next().await else {
+                return Err(Error::new("connection closed by server"));
+            };
+            match msg? {
+                Message::Text(msg) => {
+                    let response: Response = serde_json::from_str(&msg)?;
+                    if response.id != Some(id) {
+                        warn!("response of unknown request, id: {:?}", response.id);
+                        continue;
+                    }
+                    if let Some(error) = response.error {
+                        return Err(Error::Binance {
+                            http_status: response.status,
+                            code: Some(error.code),
+                            msg: error.msg,
+                        });
+                    }
+                    return Ok(serde_json::from_value(response.result.unwrap_or_default())?);
+                }
+                Message::Ping(payload) => self.stream.send(Message::Pong(payload)).await?,
+                Message::Close(Some(frame)) => return Err(close_error(&frame)),
+                Message::Close(None) => return Err(Error::new("connection closed by server")),
+                x => warn!("invalid message from server: {x:?}"),
+            }
+        }
+    }
+}
+
+/// Params of a request as a json object, `None` fields are left out
+fn into_params(params: impl Serialize) -> Result<BTreeMap<String, Value>> {
+    match serde_json::to_value(params)? {
+        Value::Null => Ok(BTreeMap::new()),
+        Value::Object(map) => Ok(map.into_iter().filter(|(_, v)| !v.is_null()).collect()),
+        _ => Err(Error::new("params of websocket api must be an object")),
+    }
+}
@@ -0,0 +1,159 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::usdm_futures::types::PositionSide;
+
+/// Round value to a multiple of step, value is returned as is if step is zero
+pub fn round_to_step(value: Decimal, step: Decimal, mode: RoundingStrategy) -> Decimal {
+    if step.is_zero() {
+        return value;
+    }
+    ((value / step).round_dp_with_strategy(0, mode) * step).normalize()
+}
+
+/// Number of decimal places of a step, e.g. 2 for `0.010`
+pub fn count_decimals(step: Decimal) -> u32 {
+    step.normalize().scale()
+}
+
+/// The `n`-th valid price above value, or value rounded up to a valid price if `n` is zero
+pub fn next_tick_up(value: Decimal, tick_size: Decimal, n: u32) -> Decimal {
+    if n == 0 {
+        return round_to_step(value, tick_size, RoundingStrategy::ToPositiveInfinity);
+    }
+    let base = round_to_step(value, tick_size, RoundingStrategy::ToNegativeInfinity);
+    (base + tick_size * Decimal::from(n)).normalize()
+}
+
+/// The `n`-th valid price below value, or value rounded down to a valid price if `n` is zero
+pub fn next_tick_down(value: Decimal, tick_size: Decimal, n: u32) -> Decimal {
+    if n == 0 {
+        return round_to_step(value, tick_size, RoundingStrategy::ToNegativeInfinity);
+    }
+    let base = round_to_step(value, tick_size, RoundingStrategy::ToPositiveInfinity);
+    (base - tick_size * Decimal::from(n)).normalize()
+}
+
+/// Unrealized profit of a position at the mark price. The quantity is signed for `BOTH` side,
+/// its absolute value is used for `LONG` and `SHORT` side.
+pub fn unrealized_pnl(
+    entry_price: Decimal,
+    mark_price: Decimal,
+    qty: Decimal,
+    side: PositionSide,
+) -> Decimal {
+    let qty = match side {
+        PositionSide::Both => qty,
+        PositionSide::Long => qty.abs(),
+        PositionSide::Short => -qty.abs(),
+    };
+    (mark_price - entry_price) * qty
+}
+
+/// Distance in percent from the mark price to the liquidation price, `None` if there is no
+/// liquidation price(zero) or the mark price is zero
+pub fn liquidation_distance_pct(
+    mark_price: Decimal,
+    liquidation_price: Decimal,
+) -> Option<Decimal> {
+    if liquidation_price.is_zero() {
+        return None;
+    }
+    let distance = (mark_price - liquidation_price).abs();
+    Some(distance.checked_div(mark_price)? * Decimal::ONE_HUNDRED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn round_to_step_by_mode() {
+        let step = dec("0.1");
+        assert_eq!(
+            round_to_step(dec("100.05"), step, RoundingStrategy::ToNegativeInfinity),
+            dec("100")
+        );
+        assert_eq!(
+            round_to_step(dec("100.05"), step, RoundingStrategy::ToPositiveInfinity),
+            dec("100.1")
+        );
+        assert_eq!(
+            round_to_step(dec("100.04"), step, RoundingStrategy::MidpointNearestEven),
+            dec("100")
+        );
+        assert_eq!(
+            round_to_step(dec("7"), dec("2.5"), RoundingStrategy::ToZero),
+            dec("5")
+        );
+        assert_eq!(
+            round_to_step(dec("1.234"), Decimal::ZERO, RoundingStrategy::ToZero),
+            dec("1.234")
+        );
+    }
+
+    #[test]
+    fn count_decimals_of_step() {
+        assert_eq!(count_decimals(dec("0.010")), 2);
+        assert_eq!(count_decimals(dec("0.1")), 1);
+        assert_eq!(count_decimals(dec("1")), 0);
+        assert_eq!(count_decimals(dec("10")), 0);
+    }
+
+    #[test]
+    fn next_ticks() {
+        let tick = dec("0.1");
+        assert_eq!(next_tick_up(dec("100.05"), tick, 0), dec("100.1"));
+        assert_eq!(next_tick_up(dec("100.05"), tick, 1), dec("100.1"));
+        assert_eq!(next_tick_up(dec("100.05"), tick, 3), dec("100.3"));
+        assert_eq!(next_tick_up(dec("100"), tick, 0), dec("100"));
+        assert_eq!(next_tick_up(dec("100"), tick, 1), dec("100.1"));
+        assert_eq!(next_tick_down(dec("100.05"), tick, 0), dec("100"));
+        assert_eq!(next_tick_down(dec("100.05"), tick, 1), dec("100"));
+        assert_eq!(next_tick_down(dec("100.05"), tick, 3), dec("99.8"));
+        assert_eq!(next_tick_down(dec("100"), tick, 0), dec("100"));
+        assert_eq!(next_tick_down(dec("100"), tick, 1), dec("99.9"));
+    }
+
+    #[test]
+    fn unrealized_pnl_by_side() {
+        let (entry, mark) = (dec("100"), dec("110"));
+        assert_eq!(
+            unrealized_pnl(entry, mark, dec("2"), PositionSide::Both),
+            dec("20")
+        );
+        assert_eq!(
+            unrealized_pnl(entry, mark, dec("-2"), PositionSide::Both),
+            dec("-20")
+        );
+        assert_eq!(
+            unrealized_pnl(entry, mark, dec("-2"), PositionSide::Long),
+            dec("20")
+        );
+        assert_eq!(
+            unrealized_pnl(entry, mark, dec("2"), PositionSide::Short),
+            dec("-20")
+        );
+        assert_eq!(
+            unrealized_pnl(entry, mark, dec("-2"), PositionSide::Short),
+            dec("-20")
+        );
+    }
+
+    #[test]
+    fn liquidation_distance() {
+        assert_eq!(
+            liquidation_distance_pct(dec("100"), dec("80")),
+            Some(dec("20"))
+        );
+        assert_eq!(
+            liquidation_distance_pct(dec("100"), dec("125")),
+            Some(dec("25"))
+        );
+        assert_eq!(liquidation_distance_pct(dec("100"), Decimal::ZERO), None);
+        assert_eq!(liquidation_distance_pct(Decimal::ZERO, dec("80")), None);
+    }
+}